@@ -0,0 +1,99 @@
+use crate::transport::RpcError;
+use crate::types::U256;
+use starknet::core::types::FieldElement;
+use std::fmt;
+
+/// Typed failures callers may want to match on. Returned inside `anyhow::Error`;
+/// use `err.downcast_ref::<ClientError>()` to inspect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// A Uint256 balance doesn't fit the u128 return type (high limb non-zero).
+    BalanceOverflow { balance: U256 },
+    /// The session key's `expires_at` has passed; nothing was signed.
+    SessionExpired { expires_at: u64 },
+    /// A call falls outside the session key's policy; nothing was signed.
+    CallNotPermitted { contract: String, selector: String },
+    /// A view call answered, but its felts didn't decode as expected. `raw` is the
+    /// response as returned, for diagnosing ABI or contract-version mismatches.
+    UnexpectedResponse { entry_point: String, reason: String, raw: Vec<FieldElement> },
+    /// Starknet RPC code 20: nothing is deployed at the address (possibly not yet).
+    ContractNotFound,
+    /// Starknet RPC code 24: the requested block doesn't exist (yet).
+    BlockNotFound,
+    /// Starknet RPC code 22, or JSON-RPC -32602: the call's parameters were rejected.
+    InvalidCallData,
+    /// Starknet RPC code 40: the contract itself failed, e.g. a revert or a
+    /// missing entry point.
+    ContractError { reason: String },
+    /// The sequencer rejected the transaction; it was never included in a block.
+    TransactionRejected { hash: FieldElement },
+    /// The transaction was included but its execution reverted; the fee was still
+    /// charged.
+    TransactionReverted { hash: FieldElement },
+}
+
+impl ClientError {
+    /// The typed form of a JSON-RPC error object, for the Starknet codes callers
+    /// react to; None for any other code.
+    ///
+    /// | code   | spec name            | variant            |
+    /// |--------|----------------------|--------------------|
+    /// | 20     | `CONTRACT_NOT_FOUND` | `ContractNotFound` |
+    /// | 22     | `INVALID_CALL_DATA`  | `InvalidCallData`  |
+    /// | 24     | `BLOCK_NOT_FOUND`    | `BlockNotFound`    |
+    /// | 40     | `CONTRACT_ERROR`     | `ContractError`    |
+    /// | -32602 | invalid params       | `InvalidCallData`  |
+    ///
+    /// `ContractError::reason` is the error's `revert_error` data when present,
+    /// else its data, else its message.
+    pub fn from_rpc(error: &RpcError) -> Option<Self> {
+        match error.code {
+            20 => Some(ClientError::ContractNotFound),
+            22 | -32602 => Some(ClientError::InvalidCallData),
+            24 => Some(ClientError::BlockNotFound),
+            40 => {
+                let reason = match &error.data {
+                    Some(data) => match data.get("revert_error").unwrap_or(data) {
+                        serde_json::Value::String(reason) => reason.clone(),
+                        other => other.to_string(),
+                    },
+                    None => error.message.clone(),
+                };
+                Some(ClientError::ContractError { reason })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::BalanceOverflow { balance } => {
+                write!(f, "Balance {} exceeds u128 (high limb {:#x})", balance, balance.high)
+            }
+            ClientError::SessionExpired { expires_at } => {
+                write!(f, "Session key expired at {}", expires_at)
+            }
+            ClientError::CallNotPermitted { contract, selector } => {
+                write!(f, "Session policy does not allow selector {} on {}", selector, contract)
+            }
+            ClientError::UnexpectedResponse { entry_point, reason, raw } => {
+                let felts: Vec<String> = raw.iter().map(|felt| format!("{:#x}", felt)).collect();
+                write!(f, "Unexpected {}() response ({}): [{}]", entry_point, reason, felts.join(", "))
+            }
+            ClientError::ContractNotFound => write!(f, "Contract not found"),
+            ClientError::BlockNotFound => write!(f, "Block not found"),
+            ClientError::InvalidCallData => write!(f, "Invalid call data"),
+            ClientError::ContractError { reason } => write!(f, "Contract error: {}", reason),
+            ClientError::TransactionRejected { hash } => {
+                write!(f, "Transaction {:#x} was rejected and not included in a block", hash)
+            }
+            ClientError::TransactionReverted { hash } => {
+                write!(f, "Transaction {:#x} reverted; its fee was still charged", hash)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
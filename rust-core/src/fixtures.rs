@@ -0,0 +1,70 @@
+//! Sample supply chain data for tests, here and downstream. Only built with the
+//! `test-fixtures` feature.
+//!
+//! [`sample_graph`] is a four-tier chain with round numbers, so expected costs
+//! can be worked out by hand:
+//!
+//! | Recipe              | Inputs      | Outputs     | Time | Facility   |
+//! |---------------------|-------------|-------------|------|------------|
+//! | `Smelt Metal`       | 4 Ore       | 2 Metal     | 30s  | `Refinery` |
+//! | `Machine Component` | 3 Metal     | 1 Component | 60s  | `Factory`  |
+//! | `Assemble Widget`   | 2 Component | 1 Widget    | 120s | `Factory`  |
+//!
+//! One Widget therefore takes 2 Components, 6 Metal and 12 Ore.
+
+use crate::supply_chain::{Recipe, SupplyChainGraph};
+use std::collections::HashMap;
+
+pub const ORE: &str = "Ore";
+pub const METAL: &str = "Metal";
+pub const COMPONENT: &str = "Component";
+pub const WIDGET: &str = "Widget";
+
+/// The Ore -> Metal -> Component -> Widget chain described in the module docs,
+/// with Ore marked raw. Holds only those three recipes, not
+/// `SupplyChainGraph::new`'s built-in steel one.
+pub fn sample_graph() -> SupplyChainGraph {
+    let mut graph: SupplyChainGraph = [
+        ("Smelt Metal", on("Refinery", recipe(&[(ORE, 4)], &[(METAL, 2)], 30))),
+        ("Machine Component", on("Factory", recipe(&[(METAL, 3)], &[(COMPONENT, 1)], 60))),
+        ("Assemble Widget", on("Factory", recipe(&[(COMPONENT, 2)], &[(WIDGET, 1)], 120))),
+    ]
+    .into_iter()
+    .map(|(name, recipe)| (name.to_string(), recipe))
+    .collect();
+    graph.mark_raw(ORE);
+    graph
+}
+
+/// Market prices for [`sample_graph`]: Ore at 1.0, each tier above worth more
+/// than its inputs.
+pub fn sample_prices() -> HashMap<String, f64> {
+    HashMap::from([
+        (ORE.to_string(), 1.0),
+        (METAL.to_string(), 2.5),
+        (COMPONENT.to_string(), 9.0),
+        (WIDGET.to_string(), 25.0),
+    ])
+}
+
+/// Resource quantities from `(name, amount)` pairs.
+pub fn qty(pairs: &[(&str, u32)]) -> HashMap<String, u32> {
+    pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+/// A recipe with no energy cost, preference or facility.
+pub fn recipe(inputs: &[(&str, u32)], outputs: &[(&str, u32)], process_time_seconds: u32) -> Recipe {
+    Recipe {
+        inputs: qty(inputs),
+        outputs: qty(outputs),
+        process_time_seconds,
+        energy_cost_kw: 0,
+        preferred: false,
+        facility: None,
+    }
+}
+
+/// `recipe` run in a `facility` building.
+pub fn on(facility: &str, recipe: Recipe) -> Recipe {
+    Recipe { facility: Some(facility.to_string()), ..recipe }
+}
@@ -35,7 +35,7 @@ impl InfluenceClient {
     }
 
     pub async fn get_asteroid(&self, asteroid_id: u64) -> Result<Asteroid> {
-        self.limiter.check().await;
+        self.limiter.check().await?;
         
         let url = format!("{}/v1/asteroids/{}", self.base_url, asteroid_id);
         let resp = self.client.get(&url)
@@ -55,7 +55,7 @@ impl InfluenceClient {
     }
 
     pub async fn fetch_unauthenticated_market_prices(&self) -> Result<std::collections::HashMap<String, f64>> {
-        self.limiter.check().await;
+        self.limiter.check().await?;
         
         let url = format!("{}/v4/encyclopedia/market_stats", self.base_url);
         // Note: Real API might need key even for public endpoints, or specific path.
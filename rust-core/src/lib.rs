@@ -1,18 +1,31 @@
+// pyo3 0.20 macros expand to impls nested inside generated functions.
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
 use std::sync::Arc;
-use tokio::runtime::Runtime;
+use tokio::runtime::Handle;
 
-mod vault;
-mod starknet_client;
-mod supply_chain;
-mod rate_limiter;
-mod influence_api;
-mod session_keys;
+pub mod error;
+pub mod vault;
+pub mod starknet_client;
+pub mod supply_chain;
+pub mod rate_limiter;
+pub mod reader;
+pub mod influence_api;
+pub mod metrics;
+pub mod network;
+pub mod session_keys;
+pub mod starknet_id;
+pub mod transport;
+pub mod types;
+pub mod units;
+#[cfg(feature = "test-fixtures")]
+pub mod fixtures;
 
 use vault::Vault;
 use starknet_client::StarknetClient;
 use supply_chain::{SupplyChainGraph, Recipe};
-use influence_api::{InfluenceClient, Asteroid};
+use influence_api::InfluenceClient;
 use session_keys::SessionKey;
 use std::collections::HashMap;
 
@@ -43,7 +56,7 @@ impl PyVault {
 #[pyclass]
 struct PyStarknetClient {
     inner: Arc<StarknetClient>,
-    rt: Runtime,
+    rt: Handle,
 }
 
 #[pymethods]
@@ -52,17 +65,27 @@ impl PyStarknetClient {
     fn new(rpc_url: Option<String>) -> PyResult<Self> {
         let url_slice = rpc_url.as_deref();
         let client = StarknetClient::new(url_slice).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
-        let rt = Runtime::new().unwrap();
+        let rt = client.blocking_handle();
         Ok(PyStarknetClient { 
             inner: Arc::new(client),
             rt 
         })
     }
 
+    /// Returns (block_number, l1_gas_price_wei).
     fn get_network_status(&self) -> PyResult<(u64, u128)> {
-        self.rt.block_on(async {
+        let status = self.rt.block_on(async {
             self.inner.get_network_status().await
-        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        Ok((status.block_number, status.l1_gas_price_wei))
+    }
+
+    /// Returns (block_number, l1_gas_price_wei, l1_gas_price_fri, is_pending).
+    fn get_network_status_full(&self) -> PyResult<(u64, u128, u128, bool)> {
+        let status = self.rt.block_on(async {
+            self.inner.get_network_status().await
+        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        Ok((status.block_number, status.l1_gas_price_wei, status.l1_gas_price_fri, status.is_pending))
     }
 
     fn get_eth_balance(&self, address: &str) -> PyResult<u128> {
@@ -71,11 +94,34 @@ impl PyStarknetClient {
         }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
 
+    /// ETH balance plus `asteroids`; returns the batch result as pretty-printed JSON.
     fn batch_query(&self, account: &str, asteroids: Vec<u64>) -> PyResult<String> {
-        self.rt.block_on(async {
-            self.inner.batch_query(account, &asteroids).await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
-        })
+        let result = self.rt.block_on(async {
+            self.inner.batch_query_account(account, &asteroids).await
+        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        serde_json::to_string_pretty(&result)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+
+    /// Takes a `BatchQueryRequest` as JSON, e.g.
+    /// `{"account": "0x...", "tokens": ["eth"], "asteroids": [1], "include_metadata": true}`,
+    /// and returns the batch result as pretty-printed JSON.
+    fn batch_query_request(&self, request_json: &str) -> PyResult<String> {
+        let request = serde_json::from_str(request_json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let result = self.rt.block_on(async {
+            self.inner.batch_query(request).await
+        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        serde_json::to_string_pretty(&result)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+
+    /// Returns (name, symbol, decimals).
+    fn get_token_metadata(&self, token: &str) -> PyResult<(String, String, u8)> {
+        let meta = self.rt.block_on(async {
+            self.inner.get_token_metadata(token).await
+        }).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        Ok((meta.name, meta.symbol, meta.decimals))
     }
 
     fn get_nonce(&self, address: &str) -> PyResult<String> {
@@ -109,6 +155,8 @@ impl PySupplyChain {
             outputs,
             process_time_seconds: time,
             energy_cost_kw: 0, // Default for manual add via Python for now
+            preferred: false,
+            facility: None,
         };
         self.inner.add_recipe(&name, recipe);
     }
@@ -121,12 +169,19 @@ impl PySupplyChain {
         self.inner.calculate_profitability(&recipe_name, &market_prices)
              .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
     }
+
+    /// Production plan for `quantity` of `target` as versioned JSON.
+    fn plan_production(&self, target: String, quantity: u32) -> PyResult<String> {
+        self.inner.production_plan(&target, quantity)
+            .and_then(|plan| plan.to_json())
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
 }
 
 #[pyclass]
 struct PyInfluenceClient {
     inner: Arc<InfluenceClient>,
-    rt: Runtime,
+    rt: Handle,
 }
 
 #[pymethods]
@@ -134,7 +189,7 @@ impl PyInfluenceClient {
     #[new]
     fn new() -> PyResult<Self> {
         let client = InfluenceClient::new().map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
-        let rt = Runtime::new().unwrap();
+        let rt = starknet_client::shared_blocking_handle();
         Ok(PyInfluenceClient {
             inner: Arc::new(client),
             rt,
@@ -171,20 +226,36 @@ struct PySessionKey {
 #[pymethods]
 impl PySessionKey {
     #[new]
-    fn new() -> PyResult<Self> {
-        let key = SessionKey::generate().map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    #[pyo3(signature = (ttl_seconds=None))]
+    fn new(ttl_seconds: Option<u64>) -> PyResult<Self> {
+        let key = match ttl_seconds {
+            Some(ttl) => SessionKey::generate_with_ttl(ttl),
+            None => SessionKey::generate(),
+        }.map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
         Ok(PySessionKey { inner: key })
     }
 
+    fn get_expires_at(&self) -> u64 {
+        self.inner.expires_at
+    }
+
     fn get_public_key(&self) -> String {
         self.inner.public_key.clone()
     }
     
-    fn create_auth_payload(&self, master_account: &str) -> String {
-        self.inner.create_authorization_payload(master_account)
+    fn create_auth_payload(&self, master_account: &str, chain_id: &str, nonce: u64) -> String {
+        self.inner.create_authorization_payload(master_account, chain_id, nonce)
     }
 }
 
+/// Format a raw base-unit amount as an exact decimal string, e.g.
+/// `format_balance(1500000000000000000, 18) == "1.5"`. Prefer this over
+/// `raw / 10**18` in Python, which goes through float and loses precision.
+#[pyfunction]
+fn format_balance(raw: u128, decimals: u8) -> String {
+    units::format_balance(raw, decimals)
+}
+
 #[pymodule]
 fn stark_pyrust_chain(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyVault>()?;
@@ -192,5 +263,6 @@ fn stark_pyrust_chain(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PySupplyChain>()?;
     m.add_class::<PyInfluenceClient>()?;
     m.add_class::<PySessionKey>()?;
+    m.add_function(wrap_pyfunction!(format_balance, m)?)?;
     Ok(())
 }
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+/// Cumulative counters for one provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderMetricsSnapshot {
+    /// Redacted endpoint (scheme + host), used to match counters back to providers on restore.
+    pub url: String,
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    /// Total time attempts spent queued on the client rate limiter, in µs. A large
+    /// share relative to `rpc_time_us` means the rate limit, not the network, is
+    /// the bottleneck.
+    #[serde(default)]
+    pub rate_limit_wait_us: u64,
+    /// Total time spent in the RPC calls themselves, in µs.
+    #[serde(default)]
+    pub rpc_time_us: u64,
+}
+
+/// Point-in-time copy of a client's counters. Serialize it on shutdown and feed it
+/// to `StarknetClientBuilder::seed_metrics` on the next start.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub providers: Vec<ProviderMetricsSnapshot>,
+}
+
+impl MetricsSnapshot {
+    pub fn total_requests(&self) -> u64 {
+        self.providers.iter().map(|p| p.requests).sum()
+    }
+}
+
+/// Per-provider counters behind a single lock, so a snapshot is consistent across
+/// providers and fields even while requests are in flight.
+pub struct ClientMetrics {
+    providers: Mutex<Vec<ProviderMetricsSnapshot>>,
+}
+
+impl ClientMetrics {
+    pub fn new(urls: &[Url]) -> Self {
+        let providers = urls.iter()
+            .map(|u| ProviderMetricsSnapshot { url: redact_url(u), ..Default::default() })
+            .collect();
+        ClientMetrics { providers: Mutex::new(providers) }
+    }
+
+    /// Add prior counters to the matching providers. Entries are matched by URL,
+    /// each seed entry used at most once; unmatched entries are dropped.
+    pub fn seed(&self, snapshot: &MetricsSnapshot) {
+        let mut providers = self.providers.lock().unwrap();
+        let mut used = vec![false; snapshot.providers.len()];
+        for current in providers.iter_mut() {
+            let found = snapshot.providers.iter().enumerate()
+                .find(|(i, prior)| !used[*i] && prior.url == current.url);
+            if let Some((i, prior)) = found {
+                used[i] = true;
+                current.requests += prior.requests;
+                current.failures += prior.failures;
+                current.retries += prior.retries;
+                current.rate_limit_wait_us += prior.rate_limit_wait_us;
+                current.rpc_time_us += prior.rpc_time_us;
+            }
+        }
+    }
+
+    /// Drop counters for providers removed from the client. `keep` is indexed
+    /// like the providers.
+    pub fn retain(&self, keep: &[bool]) {
+        let mut idx = 0;
+        self.providers.lock().unwrap().retain(|_| {
+            idx += 1;
+            keep[idx - 1]
+        });
+    }
+
+    pub fn record_attempt(&self, provider: usize, is_retry: bool, failed: bool, rate_limit_wait: Duration, rpc_time: Duration) {
+        let mut providers = self.providers.lock().unwrap();
+        let entry = &mut providers[provider];
+        entry.requests += 1;
+        entry.rate_limit_wait_us += rate_limit_wait.as_micros() as u64;
+        entry.rpc_time_us += rpc_time.as_micros() as u64;
+        if is_retry {
+            entry.retries += 1;
+        }
+        if failed {
+            entry.failures += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot { providers: self.providers.lock().unwrap().clone() }
+    }
+}
+
+/// `scheme://host[:port]` only. Provider URLs routinely embed API keys in the path
+/// or query, which must not end up in logs or persisted metrics.
+pub fn redact_url(url: &Url) -> String {
+    let host = url.host_str().unwrap_or("");
+    match url.port() {
+        Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+        None => format!("{}://{}", url.scheme(), host),
+    }
+}
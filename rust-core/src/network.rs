@@ -0,0 +1,132 @@
+use crate::types::normalize_address;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use url::Url;
+
+/// Which Starknet network a client talks to. Determines chain-specific settings
+/// such as game contract addresses.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Sepolia,
+    /// Any other chain (devnet, appchain), identified by its RPC URL. Contracts
+    /// must be configured explicitly.
+    Custom(String),
+}
+
+impl Network {
+    /// Starknet chain id short string, if known.
+    pub fn chain_id(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("SN_MAIN"),
+            Network::Sepolia => Some("SN_SEPOLIA"),
+            Network::Custom(_) => None,
+        }
+    }
+
+    /// Built-in contract addresses for this network.
+    pub fn default_config(&self) -> NetworkConfig {
+        NetworkConfig { network: self.clone(), contracts: ContractRegistry::for_network(self) }
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    /// `"mainnet"`, `"sepolia"` (case-insensitive), or an http(s) RPC URL for `Custom`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let trimmed = s.trim();
+        match trimmed.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "sepolia" => Ok(Network::Sepolia),
+            _ => match Url::parse(trimmed) {
+                Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(Network::Custom(trimmed.to_string())),
+                _ => Err(anyhow::anyhow!(
+                    "Unknown network {:?}: expected \"mainnet\", \"sepolia\", or an http(s) RPC URL",
+                    trimmed
+                )),
+            },
+        }
+    }
+}
+
+/// Chain-specific settings resolved for a client.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkConfig {
+    pub network: Network,
+    pub contracts: ContractRegistry,
+}
+
+/// Well-known contracts by logical name for one network. Addresses are stored in
+/// canonical form (see `types::felt_to_address_string`); names are case-insensitive.
+///
+/// Mainnet and sepolia are seeded with the ETH and STRK fee tokens and the Starknet
+/// ID naming contract. The game contracts (`SWAY`, `INTERACT`, `ASTEROIDS`) have
+/// names reserved but no seeded addresses yet, so register them before calling
+/// `get_asteroid` or naming them in a session policy.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ContractRegistry {
+    contracts: BTreeMap<String, String>,
+}
+
+impl ContractRegistry {
+    pub const ETH: &'static str = "eth";
+    pub const STRK: &'static str = "strk";
+    pub const SWAY: &'static str = "sway";
+    pub const INTERACT: &'static str = "interact";
+    /// Contract exposing `get_asteroid(id)`.
+    pub const ASTEROIDS: &'static str = "asteroids";
+    /// Starknet ID naming contract (`domain_to_address` / `address_to_domain`).
+    pub const STARKNET_ID: &'static str = "starknet_id";
+
+    /// Registry seeded with the known addresses for `network`. Custom networks start empty.
+    pub fn for_network(network: &Network) -> Self {
+        let seeds = match network {
+            Network::Mainnet => MAINNET_CONTRACTS,
+            Network::Sepolia => SEPOLIA_CONTRACTS,
+            Network::Custom(_) => &[],
+        };
+        let mut registry = ContractRegistry::default();
+        for (name, address) in seeds {
+            registry.register_contract(name, address).expect("valid address");
+        }
+        registry
+    }
+
+    /// Add or replace the address for `name`.
+    pub fn register_contract(&mut self, name: &str, address: &str) -> Result<()> {
+        let address = normalize_address(address)?;
+        self.contracts.insert(name.to_ascii_lowercase(), address);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.contracts.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Entries sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.contracts.iter().map(|(name, address)| (name.as_str(), address.as_str()))
+    }
+}
+
+// Add SWAY, Interact and asteroid entries here once their addresses are confirmed
+// for each network; a wrong seed would send signed transactions to the wrong contract.
+const MAINNET_CONTRACTS: &[(&str, &str)] = &[
+    (ContractRegistry::ETH, ETH_TOKEN),
+    (ContractRegistry::STRK, STRK_TOKEN),
+    (ContractRegistry::STARKNET_ID, STARKNET_ID_MAINNET),
+];
+const SEPOLIA_CONTRACTS: &[(&str, &str)] = &[
+    (ContractRegistry::ETH, ETH_TOKEN),
+    (ContractRegistry::STRK, STRK_TOKEN),
+    (ContractRegistry::STARKNET_ID, STARKNET_ID_SEPOLIA),
+];
+
+// The fee tokens share an address on mainnet and sepolia.
+const ETH_TOKEN: &str = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+const STRK_TOKEN: &str = "0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
+const STARKNET_ID_MAINNET: &str = "0x06ac597f8116f886fa1c97a23fa4e08299975ecaf6b598873ca6792b9bbfb678";
+const STARKNET_ID_SEPOLIA: &str = "0x0154bc2e1af9260b9e66af0e9c46fc757ff893b3ff6a85718a810baf1474";
@@ -1,33 +1,186 @@
 use governor::{Quota, RateLimiter};
 use governor::clock::DefaultClock;
 use governor::state::{InMemoryState, NotKeyed};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
 use std::num::NonZeroU32;
 use anyhow::{Result, Context};
 
 /// A wrapper around governor's RateLimiter to provide a simplified interface.
 /// Uses a direct (not keyed) rate limiter for global API limits.
+///
+/// Waiters are served in FIFO order: governor alone lets whichever task polls
+/// first after a refill win, which can starve others under contention, so
+/// callers queue on a fair (tokio) mutex and only the head of the queue waits
+/// on the bucket.
+///
+/// `with_reserve` sets part of the quota aside for `check_priority`, so
+/// interactive requests aren't stuck behind a backlog of background ones.
+///
+/// Clones share one bucket, so `shutdown` on any of them releases every waiter.
 #[derive(Clone)]
 pub struct ApiRateLimiter {
     limiter: Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
+    /// Permits only `check_priority` draws on; `None` without a reserve.
+    reserve: Option<Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>>,
+    reserved: u32,
+    queue: Arc<Mutex<()>>,
+    /// Mirror of the bucket for `estimated_wait`, which governor can't answer
+    /// without consuming a permit.
+    estimate: Arc<WaitEstimate>,
+    /// Never has permits; closed by `shutdown`, which wakes every `acquire`.
+    shutdown: Arc<Semaphore>,
+}
+
+/// GCRA bookkeeping matching the quota: each permit pushes the theoretical
+/// arrival time (TAT) one interval forward, and up to `burst` permits may be
+/// taken ahead of it.
+struct WaitEstimate {
+    interval: Duration,
+    burst: u32,
+    tat: std::sync::Mutex<Option<Instant>>,
+    queued: AtomicUsize,
 }
 
 impl ApiRateLimiter {
     /// Create a new rate limiter with a specified quota (requests per second).
     pub fn new(requests_per_second: u32) -> Result<Self> {
-        let nonzero = NonZeroU32::new(requests_per_second)
-            .context("Requests per second must be > 0")?;
-        
+        Self::with_reserve(requests_per_second, 0)
+    }
+
+    /// A limiter whose `requests_per_second` is split: `reserved` permits per
+    /// second are kept for `check_priority`, the rest serve `check`. The two
+    /// never add up to more than the quota. `reserved` must leave at least one
+    /// permit per second for `check`.
+    pub fn with_reserve(requests_per_second: u32, reserved: u32) -> Result<Self> {
+        let shared = requests_per_second.saturating_sub(reserved);
+        let nonzero = NonZeroU32::new(shared)
+            .context("Requests per second must be > 0 after the priority reserve")?;
+
         let quota = Quota::per_second(nonzero);
         let limiter = RateLimiter::direct(quota);
-        
+        let reserve = NonZeroU32::new(reserved).map(|n| Arc::new(RateLimiter::direct(Quota::per_second(n))));
+
         Ok(ApiRateLimiter {
             limiter: Arc::new(limiter),
+            reserve,
+            reserved,
+            queue: Arc::new(Mutex::new(())),
+            estimate: Arc::new(WaitEstimate {
+                interval: Duration::from_secs(1) / shared,
+                burst: shared,
+                tat: std::sync::Mutex::new(None),
+                queued: AtomicUsize::new(0),
+            }),
+            shutdown: Arc::new(Semaphore::new(0)),
         })
     }
 
-    /// Block (async) until a permit is available.
-    pub async fn check(&self) {
-        self.limiter.until_ready().await;
+    /// The configured quota, including any priority reserve.
+    pub fn requests_per_second(&self) -> u32 {
+        self.estimate.burst + self.reserved()
+    }
+
+    /// Permits per second set aside for `check_priority`.
+    pub fn reserved(&self) -> u32 {
+        self.reserved
+    }
+
+    /// Roughly how long a `check` started now would wait, including callers
+    /// already queued ahead of it. `Duration::ZERO` if a permit is free.
+    pub fn estimated_wait(&self) -> Duration {
+        let est = &self.estimate;
+        let now = Instant::now();
+        let tolerance = est.interval * (est.burst - 1);
+        let bucket = match *est.tat.lock().unwrap() {
+            Some(tat) => tat.saturating_duration_since(now + tolerance),
+            None => Duration::ZERO,
+        };
+        bucket + est.interval * est.queued.load(Ordering::Relaxed) as u32
+    }
+
+    /// Block (async) until a permit is available. Permits are granted in the
+    /// order callers arrived. Fails once the limiter is shut down, including for
+    /// callers already waiting.
+    ///
+    /// Cancellation-safe: dropping the future (e.g. a `tokio::select!` timeout)
+    /// leaves the queue, and a permit is only taken on the poll that returns, so
+    /// an abandoned wait never consumes one.
+    pub async fn check(&self) -> Result<()> {
+        let est = &self.estimate;
+        let queued = Queued::enter(&est.queued);
+        let permit = async {
+            let _turn = self.queue.lock().await;
+            drop(queued);
+            self.limiter.until_ready().await;
+            est.record_permit();
+        };
+        self.unless_shut_down(permit).await
+    }
+
+    /// `check` for requests that shouldn't queue behind background traffic: takes
+    /// a reserved permit if one is free, otherwise waits on the shared bucket
+    /// ahead of callers queued in `check`. Either way the total stays within the
+    /// configured quota. Cancellation-safe and fails on shutdown like `check`.
+    pub async fn check_priority(&self) -> Result<()> {
+        let permit = async {
+            if let Some(reserve) = &self.reserve {
+                if reserve.check().is_ok() {
+                    return;
+                }
+            }
+            self.limiter.until_ready().await;
+            self.estimate.record_permit();
+        };
+        self.unless_shut_down(permit).await
+    }
+
+    /// Fail every pending and future `check`/`check_priority` instead of letting
+    /// them wait for a permit, e.g. while tearing down the client that owns the
+    /// limiter. Idempotent.
+    pub fn shutdown(&self) {
+        self.shutdown.close();
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.is_closed()
+    }
+
+    /// Run `permit` to completion, or fail as soon as the limiter is shut down.
+    async fn unless_shut_down(&self, permit: impl std::future::Future<Output = ()>) -> Result<()> {
+        tokio::select! {
+            // Checked first so a limiter that's already shut down never hands out a permit.
+            biased;
+            _ = self.shutdown.acquire() => Err(anyhow::anyhow!("Rate limiter shut down")),
+            () = permit => Ok(()),
+        }
+    }
+}
+
+impl WaitEstimate {
+    /// Advance the TAT for a permit just taken from the shared bucket.
+    fn record_permit(&self) {
+        let now = Instant::now();
+        let mut tat = self.tat.lock().unwrap();
+        *tat = Some(tat.map_or(now, |t| t.max(now)) + self.interval);
+    }
+}
+
+/// Counts a caller in `WaitEstimate::queued` until dropped, including when the
+/// waiting future is cancelled.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn enter(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        Queued(count)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
     }
 }
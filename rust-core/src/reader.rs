@@ -0,0 +1,39 @@
+use crate::starknet_client::StarknetClient;
+use crate::types::NetworkStatus;
+use anyhow::Result;
+use async_trait::async_trait;
+use starknet::core::types::FieldElement;
+
+/// The read surface of [`StarknetClient`] as an object-safe trait, so callers can
+/// hold an `Arc<dyn StarknetReader>` and swap in a test double or wrap the client
+/// in a caching/logging decorator.
+#[async_trait]
+pub trait StarknetReader: Send + Sync {
+    async fn get_network_status(&self) -> Result<NetworkStatus>;
+
+    async fn get_eth_balance(&self, address: &str) -> Result<u128>;
+
+    async fn get_token_balance(&self, token: &str, address: &str) -> Result<u128>;
+
+    /// Raw view-function call by entry point name.
+    async fn call(&self, contract: &str, entry_point: &str, calldata: Vec<FieldElement>) -> Result<Vec<FieldElement>>;
+}
+
+#[async_trait]
+impl StarknetReader for StarknetClient {
+    async fn get_network_status(&self) -> Result<NetworkStatus> {
+        StarknetClient::get_network_status(self).await
+    }
+
+    async fn get_eth_balance(&self, address: &str) -> Result<u128> {
+        StarknetClient::get_eth_balance(self, address).await
+    }
+
+    async fn get_token_balance(&self, token: &str, address: &str) -> Result<u128> {
+        StarknetClient::get_token_balance(self, token, address).await
+    }
+
+    async fn call(&self, contract: &str, entry_point: &str, calldata: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        StarknetClient::call(self, contract, entry_point, calldata).await
+    }
+}
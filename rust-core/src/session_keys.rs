@@ -1,39 +1,264 @@
 use serde::{Deserialize, Serialize};
-use anyhow::Result;
+use anyhow::{Context, Result};
+use crate::types::parse_address;
+use starknet::core::crypto::{compute_hash_on_elements, ecdsa_sign, ecdsa_verify, Signature};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::signers::SigningKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-// In a real implementation, we would use:
-// use starknet::signers::{LocalWallet, SigningKey};
-// But for v0.1.0 compilation, we'll keep it simple/mocked.
+/// Source of the current Unix time for expiry checks. Swap in [`MockClock`] to
+/// test the expiry lifecycle without sleeping.
+pub trait Clock: Send + Sync {
+    fn now_unix(&self) -> u64;
+}
+
+/// The system wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> u64 {
+        // A clock set before 1970 reads as 0, i.e. nothing has expired yet.
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+/// Manually driven clock for tests.
+#[derive(Debug, Default)]
+pub struct MockClock(AtomicU64);
+
+impl MockClock {
+    pub fn new(now_unix: u64) -> Self {
+        MockClock(AtomicU64::new(now_unix))
+    }
+
+    pub fn set(&self, now_unix: u64) {
+        self.0.store(now_unix, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_unix(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Order of the Stark curve's generator; private keys lie in `[1, order)`.
+const STARK_CURVE_ORDER: &str = "0x0800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f";
+
+/// Domain separator for `SessionKey::derive`, as a Cairo short string.
+const SESSION_DERIVATION_DOMAIN: &str = "StarkPyRust.SessionDerive";
 
 #[derive(Serialize, Deserialize)]
 pub struct SessionKey {
     pub private_key: String,
     pub public_key: String,
     pub expires_at: u64,
+    /// What this key may be used to call. Unrestricted by default.
+    #[serde(default)]
+    pub policy: SessionPolicy,
+}
+
+/// Calls a session key is allowed to sign. An empty list places no restriction on
+/// that dimension, so the default policy allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionPolicy {
+    /// Contract addresses, hex, or [`ContractRegistry`](crate::network::ContractRegistry)
+    /// names such as `"interact"`, which `execute_with_session` resolves on its client.
+    #[serde(default)]
+    pub allowed_contracts: Vec<String>,
+    /// Entry point names, e.g. `"start_extraction"`.
+    #[serde(default)]
+    pub allowed_selectors: Vec<String>,
+}
+
+impl SessionPolicy {
+    /// Whether a call to `selector` on `contract` is within the policy.
+    pub fn permits(&self, contract: FieldElement, selector: FieldElement) -> Result<bool> {
+        let mut contract_ok = self.allowed_contracts.is_empty();
+        for allowed in &self.allowed_contracts {
+            contract_ok |= parse_address(allowed).context("Invalid contract in session policy")? == contract;
+        }
+        let mut selector_ok = self.allowed_selectors.is_empty();
+        for allowed in &self.allowed_selectors {
+            selector_ok |= get_selector_from_name(allowed)? == selector;
+        }
+        Ok(contract_ok && selector_ok)
+    }
 }
 
 impl SessionKey {
-    /// Generate a new ephemeral session key.
-    /// In a real implementation, this would generate a Starknet-compatible key pair.
-    /// For this v0.1.0, we simulate the structure.
+    /// Generate a new ephemeral Stark key pair from the OS RNG. Never expires
+    /// unless `expires_at` is set (see `generate_with_ttl`).
     pub fn generate() -> Result<Self> {
-        // Mock generation for compilation speed/compatibility without full crypto stack setup
-        
-        let priv_key_hex = "0x1234...ephemeral_private"; 
-        let pub_key_hex = "0x5678...ephemeral_public";
-        
-        Ok(SessionKey {
-            private_key: priv_key_hex.to_string(),
-            public_key: pub_key_hex.to_string(),
+        Self::generate_with_rng(&mut OsRng)
+    }
+
+    /// `generate` drawing randomness from `rng`, so tests can pass a seeded RNG
+    /// and get the same key every run. Production code should use `generate`.
+    ///
+    /// The private key is sampled uniformly from `[1, curve order)` by rejection:
+    /// 32 random bytes, masked to 252 bits, redrawn if out of range.
+    pub fn generate_with_rng(rng: &mut impl RngCore) -> Result<Self> {
+        let order = FieldElement::from_hex_be(STARK_CURVE_ORDER).expect("valid constant");
+        let secret = loop {
+            let mut bytes = [0u8; 32];
+            rng.try_fill_bytes(&mut bytes).map_err(|e| anyhow::anyhow!("RNG failure: {}", e))?;
+            bytes[0] &= 0x0f;
+            // Values at or above the field prime don't parse; redraw those too.
+            if let Ok(candidate) = FieldElement::from_bytes_be(&bytes) {
+                if candidate != FieldElement::ZERO && candidate < order {
+                    break candidate;
+                }
+            }
+        };
+        Ok(Self::from_secret(secret))
+    }
+
+    /// Deterministically derive session key `index` from `master_private_key`
+    /// (hex), so the same keys can be regenerated later without storing them.
+    ///
+    /// Derivation: for `attempt = 0, 1, ...` compute the Starknet array hash
+    /// `h = compute_hash_on_elements([short_string("StarkPyRust.SessionDerive"), master, index, attempt])`
+    /// (chained Pedersen with the length appended) and take the first `h` in
+    /// `[1, curve order)` as the private key. `attempt` is almost always 0.
+    pub fn derive(master_private_key: &str, index: u32, expires_at: u64) -> Result<Self> {
+        let order = FieldElement::from_hex_be(STARK_CURVE_ORDER).expect("valid constant");
+        let master = FieldElement::from_hex_be(master_private_key.trim()).context("Invalid master private key")?;
+        if master == FieldElement::ZERO || master >= order {
+            return Err(anyhow::anyhow!("Master private key must be in [1, curve order)"));
+        }
+
+        let domain = cairo_short_string_to_felt(SESSION_DERIVATION_DOMAIN).expect("valid constant");
+        let secret = (0u64..)
+            .map(|attempt| compute_hash_on_elements(&[domain, master, index.into(), attempt.into()]))
+            .find(|candidate| *candidate != FieldElement::ZERO && *candidate < order)
+            .expect("a hash lands in range");
+
+        let mut key = Self::from_secret(secret);
+        key.expires_at = expires_at;
+        Ok(key)
+    }
+
+    fn from_secret(secret: FieldElement) -> Self {
+        let signing_key = SigningKey::from_secret_scalar(secret);
+        SessionKey {
+            private_key: format!("{:#064x}", signing_key.secret_scalar()),
+            public_key: format!("{:#064x}", signing_key.verifying_key().scalar()),
             expires_at: 0, // 0 = indefinite or set later
-        })
+            policy: SessionPolicy::default(),
+        }
+    }
+
+    /// Restrict what this key may sign for (see `StarknetClient::execute_with_session`).
+    pub fn with_policy(mut self, policy: SessionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Generate a session key that expires `ttl_secs` seconds from now.
+    pub fn generate_with_ttl(ttl_secs: u64) -> Result<Self> {
+        Self::generate_with_ttl_on(&SystemClock, ttl_secs)
+    }
+
+    /// `generate_with_ttl` measured from `clock`'s current time.
+    pub fn generate_with_ttl_on(clock: &dyn Clock, ttl_secs: u64) -> Result<Self> {
+        let mut key = Self::generate()?;
+        key.expires_at = clock.now_unix().checked_add(ttl_secs).context("Session TTL overflows u64")?;
+        Ok(key)
+    }
+
+    /// Generate a session key valid for `duration`, e.g. `generate_for(Duration::from_secs(2 * 3600))`.
+    /// Sub-second precision is dropped.
+    pub fn generate_for(duration: Duration) -> Result<Self> {
+        Self::generate_with_ttl(duration.as_secs())
+    }
+
+    /// Sign `message_hash` with the session private key (Stark ECDSA).
+    pub fn sign(&self, message_hash: FieldElement) -> Result<Signature> {
+        let private_key = FieldElement::from_hex_be(&self.private_key).context("Invalid session private key")?;
+        let signature = ecdsa_sign(&private_key, &message_hash)
+            .map_err(|e| anyhow::anyhow!("Signing failed: {}", e))?;
+        Ok(Signature { r: signature.r, s: signature.s })
+    }
+
+    /// Check a signature produced by [`Self::sign`] using only the public key, e.g.
+    /// server-side. `Ok(false)` means a well-formed but non-matching signature.
+    pub fn verify(public_key_hex: &str, message_hash: FieldElement, signature: &Signature) -> Result<bool> {
+        let public_key = FieldElement::from_hex_be(public_key_hex).context("Invalid public key")?;
+        ecdsa_verify(&public_key, &message_hash, signature)
+            .map_err(|e| anyhow::anyhow!("Signature verification failed: {}", e))
+    }
+
+    /// True once the key's expiry has passed. Keys with `expires_at == 0` never expire.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_on(&SystemClock)
+    }
+
+    /// `is_expired` as of `clock`'s current time.
+    pub fn is_expired_on(&self, clock: &dyn Clock) -> bool {
+        self.expires_at != 0 && clock.now_unix() >= self.expires_at
+    }
+
+    /// Create the payload that authorizes this session key on the Interact Contract.
+    ///
+    /// `chain_id` (e.g. `SN_MAIN`) scopes the authorization to one network and `nonce`
+    /// makes each authorization unique, so a payload can't be replayed on another
+    /// chain or resubmitted later. Both are part of what gets signed.
+    pub fn create_authorization_payload(&self, master_account: &str, chain_id: &str, nonce: u64) -> String {
+        serde_json::to_string(&self.authorization(master_account, chain_id, nonce)).expect("payload serializes")
     }
 
-    /// Create the signed payload that authorizes this session key on the Interact Contract.
-    pub fn create_authorization_payload(&self, master_account: &str) -> String {
-        format!(
-            "{{ \"master\": \"{}\", \"session_pub\": \"{}\", \"action\": \"AUTHORIZE\" }}",
-            master_account, self.public_key
-        )
+    /// Structured form of [`Self::create_authorization_payload`].
+    pub fn authorization(&self, master_account: &str, chain_id: &str, nonce: u64) -> AuthorizationPayload {
+        AuthorizationPayload {
+            master: master_account.to_string(),
+            session_pub: self.public_key.clone(),
+            expires_at: self.expires_at,
+            chain_id: chain_id.to_string(),
+            nonce,
+            action: "AUTHORIZE".to_string(),
+        }
+    }
+}
+
+/// The fields a master account signs to authorize a session key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationPayload {
+    pub master: String,
+    pub session_pub: String,
+    pub expires_at: u64,
+    pub chain_id: String,
+    pub nonce: u64,
+    pub action: String,
+}
+
+/// Domain separator mixed into every authorization hash, so a signature over it
+/// can't be confused with a signature over any other message type.
+pub const AUTHORIZATION_DOMAIN: &str = "StarkPyRust.SessionAuth";
+
+impl AuthorizationPayload {
+    /// The hash the master account signs:
+    /// `H(domain, action, chain_id, master, session_pub, expires_at, nonce)`, with
+    /// `H` = Starknet `compute_hash_on_elements` and strings as Cairo short strings.
+    pub fn message_hash(&self) -> Result<FieldElement> {
+        let elements = [
+            cairo_short_string_to_felt(AUTHORIZATION_DOMAIN)?,
+            cairo_short_string_to_felt(&self.action)?,
+            cairo_short_string_to_felt(&self.chain_id)?,
+            parse_address(&self.master).context("Invalid master account address")?,
+            FieldElement::from_hex_be(&self.session_pub).context("Invalid session public key")?,
+            FieldElement::from(self.expires_at),
+            FieldElement::from(self.nonce),
+        ];
+        Ok(compute_hash_on_elements(&elements))
     }
 }
@@ -1,164 +1,1985 @@
-use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider};
+use starknet::accounts::Call;
+use starknet::core::crypto::compute_hash_on_elements;
+use starknet::core::types::{BlockId, BlockTag, BroadcastedTransaction, ContractClass, FieldElement};
+use starknet::core::utils::{cairo_short_string_to_felt, parse_cairo_short_string};
+use starknet::providers::jsonrpc::JsonRpcClientError;
+use starknet::providers::{JsonRpcClient, Provider, ProviderError};
 use url::Url;
 use anyhow::{Context, Result};
+use crate::error::ClientError;
+use crate::metrics::{ClientMetrics, MetricsSnapshot};
 use crate::rate_limiter::ApiRateLimiter;
+use crate::session_keys::{Clock, SessionKey, SessionPolicy, SystemClock};
+use crate::starknet_id;
+use crate::transport::{self, Inspector, RpcError, RpcTransport, RpcTransportError, Timeouts};
+use crate::network::{ContractRegistry, Network, NetworkConfig};
+use crate::types::{
+    felt_to_address_string, parse_address, Address, AsteroidEntry, AsteroidInfo, BalanceEntry, BatchQueryRequest, BatchQueryResult, BlockTransaction, Calldata, FeeEstimate,
+    IntoAddress, NetworkStatus, TokenMetadata, TxStatus, U256,
+};
+use std::collections::HashMap;
 use std::env;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::fmt;
+use futures::{Stream, StreamExt};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{Notify, Semaphore};
 
+/// ETH ERC20 contract (same address on mainnet and sepolia).
+pub const ETH_CONTRACT: &str = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+
+/// What a request reads the chain for; decides which block it targets when the
+/// caller passes no explicit `BlockId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPurpose {
+    /// Plain state reads (balances, view calls, block info): `Latest`.
+    Read,
+    /// Account nonce: `Pending`, since the next transaction is built on top of it.
+    Nonce,
+    /// Fee estimation: `Pending`, for the same reason as `Nonce`.
+    FeeEstimate,
+}
+
+impl BlockPurpose {
+    pub fn default_block(self) -> BlockId {
+        match self {
+            BlockPurpose::Read => BlockId::Tag(BlockTag::Latest),
+            BlockPurpose::Nonce | BlockPurpose::FeeEstimate => BlockId::Tag(BlockTag::Pending),
+        }
+    }
+
+    /// `block`, or this purpose's default when `None`.
+    pub fn resolve(self, block: Option<BlockId>) -> BlockId {
+        block.unwrap_or_else(|| self.default_block())
+    }
+}
+
+/// Environment variables scanned (in order) when no RPC URL is given explicitly.
+/// Extend with `StarknetClientBuilder::add_env_key`.
+pub const DEFAULT_ENV_KEYS: [&str; 7] = [
+    "STARKNET_RPC_URL",
+    "STARKNET_MAINNET_URL",
+    "STARKNET_LAVA_URL",
+    "STARKNET_1RPC_URL",
+    "ALCHEMY_RPC_URL",
+    "INFURA_RPC_URL",
+    "QUICKNODE_ENDPOINT",
+];
+
+/// Attempts per logical request, across all providers.
+const MAX_ATTEMPTS: u32 = 3;
+/// First exponential backoff step when the endpoint gives no `Retry-After`.
+const BASE_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Providers more than this many blocks behind the highest head seen are avoided
+/// while a closer one is available.
+pub const DEFAULT_MAX_HEAD_LAG: u64 = 10;
+
+/// Starknet produces a block every few seconds; polling faster mostly burns rate limit.
+pub const DEFAULT_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// First delay between `wait_for_acceptance` polls; doubles each poll up to
+/// `TX_POLL_MAX_INTERVAL`.
+pub const DEFAULT_TX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Cap on the `wait_for_acceptance` poll delay, as a multiple of the initial one.
+const TX_POLL_MAX_FACTOR: u32 = 8;
+
+/// Starknet RPC `TXN_HASH_NOT_FOUND`: the node hasn't seen the transaction (yet).
+const TXN_HASH_NOT_FOUND: i64 = 29;
+
+/// Outstanding requests allowed at once across all providers. High enough that
+/// only bulk fan-outs (`get_owned_token_ids` over a large collection) ever wait on it.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 256;
+
+/// Padding on the estimated fee for transactions sent by `execute_with_session`.
+const SESSION_FEE_MULTIPLIER: f64 = 1.1;
+
+/// Weight of the newest sample in the per-provider latency average.
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+
+/// Connection-level failures in a row after which a provider's transport is
+/// rebuilt, in case its pooled keep-alive connections went stale.
+pub const REBUILD_AFTER_CONNECTION_FAILURES: u32 = 3;
+
+/// Most calls `call_many` puts in one JSON-RPC batch; larger lists are split.
+/// Providers commonly cap batches around 100 entries.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// What `StarknetClientBuilder::build` does with RPC URLs that don't parse,
+/// whether they were given explicitly or found in the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConstructionMode {
+    /// Any invalid URL aborts construction with an error naming it.
+    Strict,
+    /// Invalid URLs are logged and skipped; construction fails only if none remain.
+    #[default]
+    Lenient,
+}
+
+/// How the client picks a provider for each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// Rotate through providers in order.
+    #[default]
+    RoundRobin,
+    /// Prefer the provider with the lowest average response time. Providers with
+    /// no samples yet are tried first.
+    LowestLatency,
+}
+
+impl FromStr for Selection {
+    type Err = anyhow::Error;
+
+    /// `"round-robin"` or `"lowest-latency"` (case-insensitive, `_` accepted for `-`).
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "round-robin" => Ok(Selection::RoundRobin),
+            "lowest-latency" => Ok(Selection::LowestLatency),
+            _ => Err(anyhow::anyhow!(
+                "Unknown selection {:?}: expected \"round-robin\" or \"lowest-latency\"",
+                s.trim()
+            )),
+        }
+    }
+}
+
+/// How a request is rate limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// Queues on the shared rate limit in arrival order.
+    #[default]
+    Background,
+    /// For user-facing reads: uses the permits reserved by
+    /// `StarknetClientBuilder::priority_reserve` when one is free, otherwise goes
+    /// ahead of queued background requests. Never exceeds `requests_per_second`.
+    Interactive,
+}
+
+/// Health of one provider, in circuit-breaker terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Last request succeeded (or none sent yet).
+    Closed,
+    /// Still in rotation, but the most recent requests failed.
+    HalfOpen,
+    /// Skipped by rotation: inside a `Retry-After` window, or the client is closed.
+    Open,
+}
+
+/// Point-in-time view of one provider, from [`StarknetClient::provider_states`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderState {
+    /// Redacted endpoint.
+    pub url: String,
+    pub state: BreakerState,
+    /// Failed attempts since the last success.
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Average successful response time; `None` until measured. Only tracked
+    /// with more than one provider.
+    pub latency_ms: Option<f64>,
+}
+
+/// What environment detection made of one variable. Values are never recorded,
+/// since provider URLs usually embed API keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvKeyStatus {
+    Unset,
+    /// Set, but blank after trimming.
+    Empty,
+    /// Set, but not a parseable URL.
+    InvalidUrl,
+    Accepted,
+}
+
+impl fmt::Display for EnvKeyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EnvKeyStatus::Unset => "unset",
+            EnvKeyStatus::Empty => "empty",
+            EnvKeyStatus::InvalidUrl => "invalid-url",
+            EnvKeyStatus::Accepted => "accepted",
+        })
+    }
+}
+
+/// Per-variable outcome of RPC URL detection, in scan order. Displays as
+/// `KEY=status, ...`, safe to log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectionReport {
+    pub entries: Vec<(String, EnvKeyStatus)>,
+}
+
+impl DetectionReport {
+    fn scan(keys: &[String]) -> Self {
+        let entries = keys.iter()
+            .map(|key| {
+                let status = match env::var(key) {
+                    Err(_) => EnvKeyStatus::Unset,
+                    Ok(val) if val.trim().is_empty() => EnvKeyStatus::Empty,
+                    Ok(val) if Url::parse(val.trim()).is_err() => EnvKeyStatus::InvalidUrl,
+                    Ok(_) => EnvKeyStatus::Accepted,
+                };
+                (key.clone(), status)
+            })
+            .collect();
+        DetectionReport { entries }
+    }
+}
+
+impl fmt::Display for DetectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, status)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}={}", key, status)?;
+        }
+        Ok(())
+    }
+}
+
+struct ProviderSlot {
+    /// Redacted endpoint, for reporting.
+    url: String,
+    /// `None` once the client has been closed.
+    client: RwLock<Option<Arc<JsonRpcClient<RpcTransport>>>>,
+    /// The same transport, for JSON-RPC batches, which `JsonRpcClient` can't send.
+    /// Cleared together with `client`.
+    transport: RwLock<Option<RpcTransport>>,
+    /// Set from a 429 `Retry-After`; the slot is skipped by rotation until then.
+    throttled_until: Mutex<Option<Instant>>,
+    /// Exponentially weighted average of successful response times, in ms.
+    latency_ewma_ms: Mutex<Option<f64>>,
+    /// Highest block number this provider has reported; 0 until one is seen.
+    head_block: AtomicU64,
+    /// This provider's own rate limiter, at the builder's `requests_per_second`.
+    /// `None` for local devnets unless `StarknetClientBuilder::rate_limit_local` is set.
+    limiter: Option<ApiRateLimiter>,
+    /// Connection-level failures since the last success or rebuild.
+    connection_failures: AtomicU32,
+    /// Failed attempts of any kind since the last success.
+    consecutive_failures: AtomicU32,
+    last_error: Mutex<Option<String>>,
+}
+
+impl ProviderSlot {
+    fn client(&self) -> Option<Arc<JsonRpcClient<RpcTransport>>> {
+        self.client.read().unwrap().clone()
+    }
+
+    fn transport(&self) -> Option<RpcTransport> {
+        self.transport.read().unwrap().clone()
+    }
+
+    /// Swap in a fresh transport (new connection pool) for the same endpoint.
+    /// Fails if the client was closed.
+    fn rebuild(&self) -> Result<()> {
+        let mut transport = self.transport.write().unwrap();
+        let fresh = transport.as_ref().context("client closed")?.reconnect()?;
+        *self.client.write().unwrap() = Some(Arc::new(JsonRpcClient::new(fresh.clone())));
+        *transport = Some(fresh);
+        self.connection_failures.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn record_latency(&self, elapsed: Duration) {
+        let sample = elapsed.as_secs_f64() * 1000.0;
+        let mut ewma = self.latency_ewma_ms.lock().unwrap();
+        *ewma = Some(match *ewma {
+            Some(avg) => LATENCY_EWMA_ALPHA * sample + (1.0 - LATENCY_EWMA_ALPHA) * avg,
+            None => sample,
+        });
+    }
+
+    fn latency_ms(&self) -> Option<f64> {
+        *self.latency_ewma_ms.lock().unwrap()
+    }
+
+    /// Requests per second this provider can take: its limiter's quota, or for an
+    /// unlimited (local) provider, one request per average response time, falling
+    /// back to `configured` until a response has been timed.
+    fn sustainable_rate(&self, configured: u32) -> f64 {
+        match (&self.limiter, self.latency_ms()) {
+            (Some(limiter), _) => limiter.requests_per_second() as f64,
+            (None, Some(ms)) if ms > 0.0 => 1000.0 / ms,
+            (None, _) => configured as f64,
+        }
+    }
+
+    fn observe_head(&self, block_number: u64) {
+        self.head_block.fetch_max(block_number, Ordering::Relaxed);
+    }
+
+    fn head(&self) -> Option<u64> {
+        Some(self.head_block.load(Ordering::Relaxed)).filter(|&b| b > 0)
+    }
+
+    fn throttled_for(&self, now: Instant) -> Option<Duration> {
+        let until = (*self.throttled_until.lock().unwrap())?;
+        until.checked_duration_since(now).filter(|d| !d.is_zero())
+    }
+
+    fn record_failure(&self, error: &ProviderError) {
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap() = Some(error.to_string());
+    }
+
+    /// Whether the breaker is open: closed client, or inside a `Retry-After` window.
+    fn is_open(&self, now: Instant) -> bool {
+        self.client().is_none() || self.throttled_for(now).is_some()
+    }
+
+    fn state(&self) -> ProviderState {
+        let consecutive_failures = self.consecutive_failures.load(Ordering::Relaxed);
+        let state = if self.is_open(Instant::now()) {
+            BreakerState::Open
+        } else if consecutive_failures > 0 {
+            BreakerState::HalfOpen
+        } else {
+            BreakerState::Closed
+        };
+        ProviderState {
+            url: self.url.clone(),
+            state,
+            consecutive_failures,
+            last_error: self.last_error.lock().unwrap().clone(),
+            latency_ms: self.latency_ms(),
+        }
+    }
+}
+
+/// Counts requests currently inside [`StarknetClient::request`] so `close` can wait them out.
+#[derive(Default)]
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+struct InFlightGuard<'a>(&'a InFlight);
+
+impl InFlight {
+    fn enter(&self) -> InFlightGuard<'_> {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard(self)
+    }
+
+    async fn drained(&self) {
+        loop {
+            // Registered before the check, so a guard dropping in between still wakes us.
+            let notified = self.idle.notified();
+            if self.count.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// Rotating, rate-limited Starknet JSON-RPC client.
+///
+/// Each provider owns a `reqwest` connection pool that lives as long as the
+/// client: idle keep-alive sockets stay open until the client is dropped or
+/// [`StarknetClient::close`] is called. Services that create clients per tenant
+/// should `close` them rather than relying on drop timing.
 pub struct StarknetClient {
-    providers: Vec<JsonRpcClient<HttpTransport>>,
+    providers: Vec<ProviderSlot>,
     current_index: AtomicUsize,
-    limiter: ApiRateLimiter,
+    /// Per-provider quota from the builder; see `total_rate_limit`.
+    requests_per_second: u32,
+    metrics: ClientMetrics,
+    closed: AtomicBool,
+    in_flight: InFlight,
+    config: NetworkConfig,
+    selection: Selection,
+    max_head_lag: u64,
+    block_poll_interval: Duration,
+    block_poll_jitter: f64,
+    tx_poll_interval: Duration,
+    /// Caps outstanding requests; closed by `close` so queued callers fail fast.
+    concurrency: Semaphore,
+    monotonic_reads: bool,
+    /// From `StarknetClientBuilder::runtime_handle`; the shared runtime otherwise.
+    runtime: Option<Handle>,
+    /// Decides session-key expiry in `execute_with_session`.
+    clock: Arc<dyn Clock>,
+    /// Whether the builder was given a network, rather than defaulting to mainnet.
+    network_explicit: bool,
+    /// The node's chain id, fetched once when the network can't vouch for it.
+    chain_id: tokio::sync::OnceCell<FieldElement>,
+}
+
+/// Configures a [`StarknetClient`]. `StarknetClient::new` covers the common cases;
+/// the builder is for callers that need more control.
+pub struct StarknetClientBuilder {
+    rpc_urls: Vec<String>,
+    env_keys: Vec<String>,
+    network: Network,
+    network_explicit: bool,
+    contracts: Vec<(String, String)>,
+    selection: Selection,
+    requests_per_second: u32,
+    priority_reserve: f64,
+    seed_metrics: Option<MetricsSnapshot>,
+    user_agent: String,
+    timeouts: Timeouts,
+    construction: ConstructionMode,
+    max_head_lag: u64,
+    block_poll_interval: Duration,
+    block_poll_jitter: f64,
+    tx_poll_interval: Duration,
+    rate_limit_local: bool,
+    max_concurrency: usize,
+    monotonic_reads: bool,
+    runtime: Option<Handle>,
+    inspector: Option<Inspector>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for StarknetClientBuilder {
+    fn default() -> Self {
+        StarknetClientBuilder {
+            rpc_urls: Vec::new(),
+            env_keys: DEFAULT_ENV_KEYS.iter().map(|k| k.to_string()).collect(),
+            network: Network::default(),
+            network_explicit: false,
+            contracts: Vec::new(),
+            selection: Selection::default(),
+            // Safe default: 5 requests per second (typical free tier)
+            requests_per_second: 5,
+            priority_reserve: 0.0,
+            seed_metrics: None,
+            user_agent: transport::DEFAULT_USER_AGENT.to_string(),
+            timeouts: Timeouts::default(),
+            construction: ConstructionMode::default(),
+            max_head_lag: DEFAULT_MAX_HEAD_LAG,
+            block_poll_interval: DEFAULT_BLOCK_POLL_INTERVAL,
+            block_poll_jitter: 0.0,
+            tx_poll_interval: DEFAULT_TX_POLL_INTERVAL,
+            rate_limit_local: false,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            monotonic_reads: false,
+            runtime: None,
+            inspector: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl StarknetClientBuilder {
+    /// Add an explicit RPC URL. If none are added, URLs are detected from the environment.
+    pub fn rpc_url(mut self, url: &str) -> Self {
+        self.rpc_urls.push(url.to_string());
+        self
+    }
+
+    /// Also scan `key` during environment detection, after the keys already listed.
+    pub fn add_env_key(mut self, key: &str) -> Self {
+        if !self.env_keys.iter().any(|k| k == key) {
+            self.env_keys.push(key.to_string());
+        }
+        self
+    }
+
+    /// Replace the detection list entirely (including [`DEFAULT_ENV_KEYS`]).
+    pub fn env_keys(mut self, keys: &[&str]) -> Self {
+        self.env_keys = keys.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// Target network (default mainnet). `Network::Custom(url)` also supplies the
+    /// RPC URL when none is added explicitly. Without it, transactions are signed
+    /// for the chain id the node reports, and fail if that isn't mainnet.
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self.network_explicit = true;
+        self
+    }
+
+    /// Override the Starknet ID naming contract used by `resolve_starknet_id`
+    /// and `address_to_name`. Seeded for mainnet and sepolia.
+    pub fn starknet_id_contract(self, address: &str) -> Self {
+        self.register_contract(ContractRegistry::STARKNET_ID, address)
+    }
+
+    /// Override the asteroid contract used by `get_asteroid`.
+    pub fn asteroid_contract(self, address: &str) -> Self {
+        self.register_contract(ContractRegistry::ASTEROIDS, address)
+    }
+
+    /// Add or override a [`ContractRegistry`] entry on top of the network's defaults.
+    /// Invalid addresses are reported by `build`.
+    pub fn register_contract(mut self, name: &str, address: &str) -> Self {
+        self.contracts.push((name.to_string(), address.to_string()));
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Rate limit applied to each provider separately (default 5, a typical free tier).
+    pub fn requests_per_second(mut self, rps: u32) -> Self {
+        self.requests_per_second = rps;
+        self
+    }
+
+    /// Fraction of `requests_per_second` (rounded up to whole permits) kept for
+    /// `Priority::Interactive` requests, so they don't wait behind background
+    /// polling. Background requests get what's left. Default 0; must be below 1.
+    pub fn priority_reserve(mut self, fraction: f64) -> Self {
+        self.priority_reserve = fraction;
+        self
+    }
+
+    /// Also apply `requests_per_second` to localhost endpoints (katana, devnet).
+    /// Off by default: local nodes have no tier limits to protect.
+    pub fn rate_limit_local(mut self, enabled: bool) -> Self {
+        self.rate_limit_local = enabled;
+        self
+    }
+
+    /// Restore counters from a previous run's [`StarknetClient::metrics_snapshot`],
+    /// so cumulative metrics survive a restart.
+    pub fn seed_metrics(mut self, snapshot: MetricsSnapshot) -> Self {
+        self.seed_metrics = Some(snapshot);
+        self
+    }
+
+    /// `User-Agent` sent to every provider (default [`transport::DEFAULT_USER_AGENT`]).
+    /// Some providers throttle anonymous clients or ask for an app name in support requests.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// How long to wait for a provider connection, TCP plus TLS handshake
+    /// (default [`transport::DEFAULT_CONNECT_TIMEOUT`], 10s). Keep it short to
+    /// move on from unreachable hosts quickly.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = timeout;
+        self
+    }
+
+    /// How long one request may take end to end, including connecting
+    /// (default [`transport::DEFAULT_REQUEST_TIMEOUT`], 30s). Raise it for
+    /// large queries such as blocks with transactions.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.request = timeout;
+        self
+    }
+
+    /// Most requests in flight at once, client-wide (default [`DEFAULT_MAX_CONCURRENCY`]).
+    /// Complements `requests_per_second`: the rate limit bounds throughput, this
+    /// bounds open sockets and buffered responses. Extra callers queue.
+    pub fn max_concurrency(mut self, max: usize) -> Self {
+        self.max_concurrency = max;
+        self
+    }
+
+    /// How many blocks a provider may trail the highest observed head before
+    /// selection avoids it (default [`DEFAULT_MAX_HEAD_LAG`]).
+    pub fn max_head_lag(mut self, blocks: u64) -> Self {
+        self.max_head_lag = blocks;
+        self
+    }
+
+    /// Never let `get_network_status` go backwards: a head below the highest block
+    /// already seen (a provider behind the others) is retried on another provider,
+    /// and an error if all attempts come back behind. Off by default.
+    pub fn monotonic_reads(mut self, enabled: bool) -> Self {
+        self.monotonic_reads = enabled;
+        self
+    }
+
+    /// Call `inspector(method, request, response)` with the raw JSON of every
+    /// RPC exchange, for logging or asserting on traffic (see [`Inspector`]).
+    /// Off by default.
+    pub fn inspector(mut self, inspector: impl Fn(&str, &serde_json::Value, &serde_json::Value) + Send + Sync + 'static) -> Self {
+        self.inspector = Some(Inspector::new(inspector));
+        self
+    }
+
+    /// Clock `execute_with_session` checks session-key expiry against (default
+    /// [`SystemClock`]). Pass a shared [`MockClock`](crate::session_keys::MockClock)
+    /// to test expiry deterministically.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Runtime returned by `StarknetClient::blocking_handle`, e.g. one the
+    /// application already runs. Defaults to a lazily created shared runtime.
+    pub fn runtime_handle(mut self, handle: Handle) -> Self {
+        self.runtime = Some(handle);
+        self
+    }
+
+    /// How often `block_stream` polls for a new head (default [`DEFAULT_BLOCK_POLL_INTERVAL`]).
+    pub fn block_poll_interval(mut self, interval: Duration) -> Self {
+        self.block_poll_interval = interval;
+        self
+    }
+
+    /// First delay between `wait_for_acceptance` polls (default
+    /// [`DEFAULT_TX_POLL_INTERVAL`]). It doubles after every poll, up to 8x.
+    pub fn tx_poll_interval(mut self, interval: Duration) -> Self {
+        self.tx_poll_interval = interval;
+        self
+    }
+
+    /// Randomize each `block_stream` sleep by up to `fraction` of the poll
+    /// interval either way (0.2 = ±20%), so pollers sharing a provider account
+    /// drift apart instead of hitting it in lockstep. Default 0 (fixed interval);
+    /// must be within 0..=1.
+    pub fn block_poll_jitter(mut self, fraction: f64) -> Self {
+        self.block_poll_jitter = fraction;
+        self
+    }
+
+    pub fn construction(mut self, mode: ConstructionMode) -> Self {
+        self.construction = mode;
+        self
+    }
+
+    /// What environment detection would find for each configured key right now
+    /// (after loading `.env`), without building anything.
+    pub fn detection_report(&self) -> DetectionReport {
+        dotenv::dotenv().ok();
+        DetectionReport::scan(&self.env_keys)
+    }
+
+    pub fn build(self) -> Result<StarknetClient> {
+        // Load .env if not already loaded
+        dotenv::dotenv().ok();
+
+        // (label for messages, raw value). Env-sourced URLs are labelled by variable
+        // name only, since the value may embed an API key.
+        let candidates: Vec<(String, String)> = match (&self.network, self.rpc_urls.is_empty()) {
+            (_, false) => self.rpc_urls.iter().map(|u| (u.clone(), u.clone())).collect(),
+            (Network::Custom(url), true) => vec![(url.clone(), url.clone())],
+            _ => StarknetClient::detect_rpc_urls(&self.env_keys)?,
+        };
+
+        let from_env = self.rpc_urls.is_empty() && !matches!(self.network, Network::Custom(_));
+
+        let mut config = self.network.default_config();
+        for (name, address) in &self.contracts {
+            config.contracts.register_contract(name, address)
+                .context(format!("Invalid address for contract {:?}", name))?;
+        }
+
+        let mut urls = Vec::new();
+        for (label, raw) in candidates {
+            match Url::parse(&raw) {
+                Ok(url) => urls.push(url),
+                Err(e) if self.construction == ConstructionMode::Strict => {
+                    return Err(anyhow::anyhow!("Invalid RPC URL {}: {}", label, e));
+                }
+                Err(e) => log::warn!("Skipping invalid RPC URL {}: {}", label, e),
+            }
+        }
+
+        if urls.is_empty() {
+            if from_env {
+                return Err(anyhow::anyhow!(
+                    "No valid RPC providers available (checked {})",
+                    DetectionReport::scan(&self.env_keys)
+                ));
+            }
+            return Err(anyhow::anyhow!("No valid RPC providers available."));
+        }
+
+        let metrics = ClientMetrics::new(&urls);
+        if let Some(snapshot) = &self.seed_metrics {
+            metrics.seed(snapshot);
+        }
+
+        if self.timeouts.connect.is_zero() || self.timeouts.request.is_zero() {
+            return Err(anyhow::anyhow!("Connect and request timeouts must be > 0"));
+        }
+        if !(0.0..1.0).contains(&self.priority_reserve) {
+            return Err(anyhow::anyhow!("Priority reserve must be within 0..1, got {}", self.priority_reserve));
+        }
+        let reserved = (self.requests_per_second as f64 * self.priority_reserve).ceil() as u32;
+        let new_limiter = || ApiRateLimiter::with_reserve(self.requests_per_second, reserved);
+        // Checked up front so the quota is validated even when every provider is local.
+        new_limiter()?;
+
+        let mut providers = Vec::with_capacity(urls.len());
+        for url in urls {
+            let redacted = crate::metrics::redact_url(&url);
+            let limiter = if self.rate_limit_local || !is_local_url(&url) { Some(new_limiter()?) } else { None };
+            let transport = RpcTransport::new(url, &self.user_agent, self.timeouts)
+                .context(format!("Invalid user agent: {:?}", self.user_agent))?
+                .inspect(self.inspector.clone());
+            providers.push(ProviderSlot {
+                url: redacted,
+                client: RwLock::new(Some(Arc::new(JsonRpcClient::new(transport.clone())))),
+                transport: RwLock::new(Some(transport)),
+                throttled_until: Mutex::new(None),
+                latency_ewma_ms: Mutex::new(None),
+                head_block: AtomicU64::new(0),
+                limiter,
+                connection_failures: AtomicU32::new(0),
+                consecutive_failures: AtomicU32::new(0),
+                last_error: Mutex::new(None),
+            });
+        }
+
+        if self.max_concurrency == 0 {
+            return Err(anyhow::anyhow!("Max concurrency must be > 0"));
+        }
+        if !(0.0..=1.0).contains(&self.block_poll_jitter) {
+            return Err(anyhow::anyhow!("Block poll jitter must be within 0..=1, got {}", self.block_poll_jitter));
+        }
+
+        Ok(StarknetClient { 
+            providers, 
+            current_index: AtomicUsize::new(0),
+            requests_per_second: self.requests_per_second,
+            metrics,
+            closed: AtomicBool::new(false),
+            in_flight: InFlight::default(),
+            config,
+            selection: self.selection,
+            max_head_lag: self.max_head_lag,
+            block_poll_interval: self.block_poll_interval,
+            block_poll_jitter: self.block_poll_jitter,
+            tx_poll_interval: self.tx_poll_interval,
+            concurrency: Semaphore::new(self.max_concurrency),
+            monotonic_reads: self.monotonic_reads,
+            runtime: self.runtime,
+            clock: self.clock,
+            network_explicit: self.network_explicit,
+            chain_id: tokio::sync::OnceCell::new(),
+        })
+    }
+
+    /// `build`, then probe every provider with a `blockNumber` call and drop the
+    /// ones that don't answer. Fails if none do, so a dead configuration shows up
+    /// at startup rather than on the first real request.
+    pub async fn connect(self) -> Result<StarknetClient> {
+        let mut client = self.build()?;
+        client.drop_unreachable_providers().await?;
+        Ok(client)
+    }
 }
 
-impl StarknetClient {
-    /// Create a new StarknetClient. 
-    /// If `rpc_url` is provided, it uses ONLY that one.
-    /// Otherwise, it detects ALL compatible URLs in the environment and rotates between them.
-    pub fn new(rpc_url: Option<&str>) -> Result<Self> {
-        // Load .env if not already loaded
-        dotenv::dotenv().ok();
+/// Process-wide runtime behind `StarknetClient::blocking_handle`, created on first
+/// use. Multi-threaded (one worker) because `Handle::block_on` can't drive the IO
+/// and timer drivers of a current-thread runtime.
+pub(crate) fn shared_blocking_handle() -> Handle {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("stark-pyrust-blocking")
+                .enable_all()
+                .build()
+                .expect("failed to start the shared tokio runtime")
+        })
+        .handle()
+        .clone()
+}
+
+impl StarknetClient {
+    /// Handle for driving this client from synchronous code:
+    /// `client.blocking_handle().block_on(client.get_nonce(addr))`. Every client
+    /// shares one runtime unless the builder was given its own, so scripts making
+    /// many calls don't pay for a new runtime each time. Must not be called from
+    /// inside an async context.
+    pub fn blocking_handle(&self) -> Handle {
+        self.runtime.clone().unwrap_or_else(shared_blocking_handle)
+    }
+
+    /// Create a new StarknetClient. 
+    /// If `rpc_url` is provided, it uses ONLY that one.
+    /// Otherwise, it detects ALL compatible URLs in the environment and rotates between them.
+    pub fn new(rpc_url: Option<&str>) -> Result<Self> {
+        let mut builder = Self::builder();
+        if let Some(u) = rpc_url {
+            builder = builder.rpc_url(u);
+        }
+        builder.build()
+    }
+
+    /// `new`, but only returns once at least one provider has answered a probe;
+    /// unreachable providers are dropped. See [`StarknetClientBuilder::connect`].
+    pub async fn connect(rpc_url: Option<&str>) -> Result<Self> {
+        let mut builder = Self::builder();
+        if let Some(u) = rpc_url {
+            builder = builder.rpc_url(u);
+        }
+        builder.connect().await
+    }
+
+    /// Probe each provider directly (bypassing rotation and retries) and keep only
+    /// those that respond.
+    async fn drop_unreachable_providers(&mut self) -> Result<()> {
+        let probes = self.providers.iter().map(|slot| async move {
+            if let Some(limiter) = &slot.limiter {
+                limiter.check().await?;
+            }
+            let client = slot.client().context("client closed")?;
+            let head = client.block_number().await?;
+            slot.observe_head(head);
+            anyhow::Ok(())
+        });
+        let results = futures::future::join_all(probes).await;
+
+        let mut failures = Vec::new();
+        for (slot, result) in self.providers.iter().zip(&results) {
+            if let Err(e) = result {
+                log::warn!("Dropping unreachable RPC provider {}: {}", slot.url, e);
+                failures.push(format!("{}: {}", slot.url, e));
+            }
+        }
+        if failures.len() == self.providers.len() {
+            return Err(anyhow::anyhow!("No RPC provider responded ({})", failures.join("; ")));
+        }
+
+        let keep: Vec<bool> = results.iter().map(Result::is_ok).collect();
+        let mut idx = 0;
+        self.providers.retain(|_| {
+            idx += 1;
+            keep[idx - 1]
+        });
+        self.metrics.retain(&keep);
+        Ok(())
+    }
+
+    /// Single-provider client from exactly one environment variable of the caller's
+    /// choosing (e.g. `MY_APP_STARKNET`), skipping the built-in detection list.
+    pub fn from_env_var(name: &str) -> Result<Self> {
+        dotenv::dotenv().ok();
+
+        let val = env::var(name).context(format!("Environment variable {} is not set", name))?;
+        let trimmed = val.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow::anyhow!("Environment variable {} is empty", name));
+        }
+        Url::parse(trimmed).context(format!("Environment variable {} is not a valid URL", name))?;
+
+        Self::builder().rpc_url(trimmed).build()
+    }
+
+    pub fn builder() -> StarknetClientBuilder {
+        StarknetClientBuilder::default()
+    }
+
+    /// Stop accepting requests, fail those still waiting on the rate limiter, wait
+    /// for in-flight ones to finish, then drop every provider's transport so its
+    /// pooled connections are released. Later calls fail with a "client closed"
+    /// error. Idempotent.
+    pub async fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.concurrency.close();
+        // Requests waiting on a permit count as in flight; release them so the drain finishes.
+        for limiter in self.providers.iter().filter_map(|slot| slot.limiter.as_ref()) {
+            limiter.shutdown();
+        }
+        self.in_flight.drained().await;
+        // Transport first: `ProviderSlot::rebuild` holds it while replacing the client.
+        for slot in &self.providers {
+            slot.transport.write().unwrap().take();
+            slot.client.write().unwrap().take();
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// How long a request would wait for a permit on the least busy provider, so
+    /// a scheduler can do other work first. `Duration::ZERO` if it could go out
+    /// now. Doesn't account for providers parked by `Retry-After`; local providers
+    /// skipping the limiter never wait.
+    pub fn estimated_wait(&self) -> Duration {
+        self.providers.iter()
+            .map(|slot| slot.limiter.as_ref().map_or(Duration::ZERO, ApiRateLimiter::estimated_wait))
+            .min()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Sum of every provider's configured requests per second: each provider has
+    /// its own limiter at the builder's `requests_per_second`. Local providers
+    /// that skip rate limiting count at that rate too.
+    pub fn total_rate_limit(&self) -> u32 {
+        self.providers.iter()
+            .map(|slot| slot.limiter.as_ref().map_or(self.requests_per_second, ApiRateLimiter::requests_per_second))
+            .sum()
+    }
+
+    /// `total_rate_limit` as it stands right now: providers with an open breaker
+    /// (parked by `Retry-After`, or the client closed) contribute 0, and local
+    /// providers count at one request per average response time (the configured
+    /// rate until one has been timed). Size work queues to this rather than to the
+    /// configured rate.
+    pub fn effective_throughput_estimate(&self) -> f64 {
+        let now = Instant::now();
+        self.providers.iter()
+            .filter(|slot| !slot.is_open(now))
+            .map(|slot| slot.sustainable_rate(self.requests_per_second))
+            .sum()
+    }
+
+    /// Address registered under `name` (see [`ContractRegistry`]).
+    pub fn contract_address(&self, name: &str) -> Option<&str> {
+        self.config.contracts.get(name)
+    }
+
+    pub fn network_config(&self) -> &NetworkConfig {
+        &self.config
+    }
+
+    /// Consistent copy of the per-provider request/failure/retry counters.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Non-empty values of `keys`, as (`"$KEY"`, value) pairs. Validation is left to
+    /// the builder so it follows the configured [`ConstructionMode`].
+    fn detect_rpc_urls(keys: &[String]) -> Result<Vec<(String, String)>> {
+        let mut urls = Vec::new();
+        for key in keys {
+            if let Ok(val) = env::var(key) {
+                let trimmed = val.trim();
+                if !trimmed.is_empty() {
+                    urls.push((format!("${}", key), trimmed.to_string()));
+                }
+            }
+        }
+        
+        if urls.is_empty() {
+            Err(anyhow::anyhow!(
+                "No RPC URL found in environment variables (checked {})",
+                DetectionReport::scan(keys)
+            ))
+        } else {
+            Ok(urls)
+        }
+    }
+
+    /// How far each provider trails the highest block any provider has reported,
+    /// keyed by redacted URL. `None` until that provider has reported a block.
+    pub fn provider_head_lag(&self) -> Vec<(String, Option<u64>)> {
+        let max_head = self.providers.iter().filter_map(ProviderSlot::head).max();
+        self.providers.iter()
+            .map(|slot| (slot.url.clone(), slot.head().zip(max_head).map(|(head, max)| max - head)))
+            .collect()
+    }
+
+    /// Breaker state, failure streak, last error and latency of every provider,
+    /// in configuration order. Meant for status pages.
+    pub fn provider_states(&self) -> Vec<ProviderState> {
+        self.providers.iter().map(ProviderSlot::state).collect()
+    }
+
+    /// Pick the next provider per the [`Selection`] policy, skipping any still
+    /// inside a `Retry-After` window and preferring ones within `max_head_lag` of
+    /// the chain head. If every provider is throttled, returns the one that frees up
+    /// first together with how long to wait for it.
+    fn next_provider(&self) -> (usize, Option<Duration>) {
+        let now = Instant::now();
+        // Nothing to choose between: skip the shared counter and the ranking.
+        if let [only] = self.providers.as_slice() {
+            return (0, only.throttled_for(now));
+        }
+        let start = self.current_index.fetch_add(1, Ordering::Relaxed);
+        let max_head = self.providers.iter().filter_map(ProviderSlot::head).max().unwrap_or(0);
+        let mut soonest: Option<(usize, Duration)> = None;
+        let mut best: Option<(usize, (bool, f64))> = None;
+
+        for offset in 0..self.providers.len() {
+            let idx = (start + offset) % self.providers.len();
+            let slot = &self.providers[idx];
+            match slot.throttled_for(now) {
+                None => {
+                    let lagging = slot.head().is_some_and(|head| max_head - head > self.max_head_lag);
+                    let latency = match self.selection {
+                        Selection::RoundRobin if !lagging => return (idx, None),
+                        // Ties keep rotation order.
+                        Selection::RoundRobin => 0.0,
+                        // Unmeasured providers sort first so every endpoint gets sampled.
+                        Selection::LowestLatency => slot.latency_ms().unwrap_or(-1.0),
+                    };
+                    let key = (lagging, latency);
+                    if best.is_none_or(|(_, b)| key < b) {
+                        best = Some((idx, key));
+                    }
+                }
+                Some(wait) => {
+                    if soonest.is_none_or(|(_, best)| wait < best) {
+                        soonest = Some((idx, wait));
+                    }
+                }
+            }
+        }
+
+        if let Some((idx, _)) = best {
+            return (idx, None);
+        }
+        let (idx, wait) = soonest.expect("at least one provider");
+        (idx, Some(wait))
+    }
+
+    /// The JSON-RPC client of the next provider in rotation, for provider methods
+    /// this crate doesn't wrap. Calls made on it bypass the rate limiter, retries,
+    /// `Retry-After` parking and metrics; use `with_provider` to keep those.
+    /// Fails once the client is closed.
+    pub fn raw_provider(&self) -> Result<Arc<JsonRpcClient<RpcTransport>>> {
+        if self.is_closed() {
+            return Err(anyhow::anyhow!("client closed"));
+        }
+        let (idx, _) = self.next_provider();
+        self.providers[idx].client().context("client closed")
+    }
+
+    /// Replace provider `index`'s transport with a fresh one (new connection
+    /// pool), for endpoints that recovered while their pooled connections went
+    /// stale. The client also does this itself after
+    /// `REBUILD_AFTER_CONNECTION_FAILURES` connection failures in a row.
+    pub fn rebuild_provider(&self, index: usize) -> Result<()> {
+        let slot = self.providers.get(index)
+            .with_context(|| format!("No provider at index {} ({} configured)", index, self.providers.len()))?;
+        slot.rebuild()
+    }
+
+    /// Run `op` against the rotation like the crate's own reads: rate limited,
+    /// retried on transient failures and counted in metrics. `op` may be called
+    /// once per attempt, each time with the provider chosen for it.
+    pub async fn with_provider<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: Fn(Arc<JsonRpcClient<RpcTransport>>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.request(op).await.map_err(|e| rpc_failure("Provider call failed", e))
+    }
+
+    /// Run one RPC against the rotation, retrying transient failures.
+    ///
+    /// A 429 carrying `Retry-After` parks that provider for the requested time and
+    /// the retry goes to another provider immediately; with no alternative, we
+    /// sleep out the window. Other transient errors use exponential backoff.
+    async fn request<T, F, Fut>(&self, op: F) -> Result<T, ProviderError>
+    where
+        F: Fn(Arc<JsonRpcClient<RpcTransport>>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.request_on(op).await.map(|(_, value)| value)
+    }
+
+    /// `request`, also returning the index of the provider that answered.
+    async fn request_on<T, F, Fut>(&self, op: F) -> Result<(usize, T), ProviderError>
+    where
+        F: Fn(Arc<JsonRpcClient<RpcTransport>>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.request_via(Priority::Background, ProviderSlot::client, op).await
+    }
+
+    /// The retry loop behind `request_on`, handing `op` whatever `connect` takes
+    /// from the chosen slot (the JSON-RPC client, or the raw transport for batches).
+    async fn request_via<C, T, F, Fut>(
+        &self,
+        priority: Priority,
+        connect: fn(&ProviderSlot) -> Option<C>,
+        op: F,
+    ) -> Result<(usize, T), ProviderError>
+    where
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let _in_flight = self.in_flight.enter();
+        let closed = || ProviderError::from(JsonRpcClientError::TransportError(RpcTransportError::Closed));
+        let _permit = self.concurrency.acquire().await.map_err(|_| closed())?;
+
+        let mut attempt = 0;
+        loop {
+            if self.is_closed() {
+                return Err(closed());
+            }
+            let (idx, throttled) = self.next_provider();
+            let slot = &self.providers[idx];
+            if let Some(wait) = throttled {
+                tokio::time::sleep(wait).await;
+            }
+            let queued = Instant::now();
+            if let Some(limiter) = &slot.limiter {
+                let permit = match priority {
+                    Priority::Background => limiter.check().await,
+                    Priority::Interactive => limiter.check_priority().await,
+                };
+                permit.map_err(|_| closed())?;
+            }
+            let rate_limit_wait = queued.elapsed();
+
+            let client = connect(slot).ok_or_else(closed)?;
+            let started = Instant::now();
+            let result = op(client).await;
+            let rpc_time = started.elapsed();
+            // Latency only feeds provider ranking, which a single provider doesn't need.
+            if result.is_ok() && self.providers.len() > 1 {
+                slot.record_latency(rpc_time);
+            }
+            self.metrics.record_attempt(idx, attempt > 0, result.is_err(), rate_limit_wait, rpc_time);
+            #[cfg(feature = "tracing")]
+            tracing::debug_span!(
+                "rpc_attempt",
+                provider = %slot.url,
+                attempt,
+                rate_limit_wait_ms = rate_limit_wait.as_millis() as u64,
+                rpc_ms = rpc_time.as_millis() as u64,
+                failed = result.is_err(),
+            )
+            .in_scope(|| tracing::debug!("rpc attempt finished"));
+            let err = match result {
+                Ok(value) => {
+                    slot.connection_failures.store(0, Ordering::Relaxed);
+                    slot.consecutive_failures.store(0, Ordering::Relaxed);
+                    return Ok((idx, value));
+                }
+                Err(e) => e,
+            };
+            slot.record_failure(&err);
+            if transport::is_connection_error(&err)
+                && slot.connection_failures.fetch_add(1, Ordering::Relaxed) + 1 >= REBUILD_AFTER_CONNECTION_FAILURES
+            {
+                log::warn!("Rebuilding transport for {} after repeated connection failures", slot.url);
+                if let Err(e) = slot.rebuild() {
+                    log::warn!("Could not rebuild transport for {}: {}", slot.url, e);
+                }
+            }
+
+            attempt += 1;
+            if attempt >= MAX_ATTEMPTS || !transport::is_retryable(&err) {
+                return Err(err);
+            }
 
-        let mut url_strings = Vec::new();
+            match transport::retry_after(&err) {
+                Some(delay) => {
+                    *slot.throttled_until.lock().unwrap() = Some(Instant::now() + delay);
+                }
+                None => tokio::time::sleep(BASE_BACKOFF * 2u32.pow(attempt - 1)).await,
+            }
+        }
+    }
 
-        if let Some(u) = rpc_url {
-            url_strings.push(u.to_string());
-        } else {
-            url_strings = Self::detect_rpc_urls()?;
+    pub async fn get_network_status(&self) -> Result<NetworkStatus> {
+        let latest = BlockPurpose::Read.default_block();
+        if !self.monotonic_reads {
+            return self.block_status(latest).await;
+        }
+
+        let mut behind = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let floor = self.providers.iter().filter_map(ProviderSlot::head).max().unwrap_or(0);
+            let status = self.block_status(latest).await?;
+            if status.is_pending || status.block_number >= floor {
+                return Ok(status);
+            }
+            behind = Some((status.block_number, floor));
         }
+        let (number, floor) = behind.expect("at least one attempt");
+        Err(anyhow::anyhow!("Providers keep reporting block {} behind already-seen block {}", number, floor))
+    }
+
+    /// Number and gas prices of block `block_id`.
+    async fn block_status(&self, block_id: BlockId) -> Result<NetworkStatus> {
+        self.block_finality(block_id).await.map(|(status, _)| status)
+    }
 
-        let mut providers = Vec::new();
-        for url_str in url_strings {
-            let url = Url::parse(&url_str).context(format!("Invalid RPC URL: {}", url_str))?;
-            providers.push(JsonRpcClient::new(HttpTransport::new(url)));
+    /// The most recent block already accepted on L1, i.e. no longer subject to
+    /// L2 reorgs; use it for settlement decisions instead of `get_network_status`.
+    /// L1 acceptance only ever moves forward, so this walks back from the head in
+    /// doubling steps and then bisects: about `2 * log2(lag)` block reads for a
+    /// head `lag` blocks ahead of L1. Fails if no block has reached L1 yet.
+    pub async fn get_l1_accepted_status(&self) -> Result<NetworkStatus> {
+        let (head, on_l1) = self.block_finality(BlockPurpose::Read.default_block()).await?;
+        if on_l1 {
+            return Ok(head);
         }
 
-        if providers.is_empty() {
-             return Err(anyhow::anyhow!("No valid RPC providers available."));
+        // `hi` is known not to be on L1; find an older block that is.
+        let mut hi = head.block_number;
+        let mut step = 1;
+        let (mut lo, mut best) = loop {
+            if hi == 0 {
+                return Err(anyhow::anyhow!("No block has been accepted on L1 yet"));
+            }
+            let probe = hi.saturating_sub(step);
+            let (status, on_l1) = self.block_finality(BlockId::Number(probe)).await?;
+            if on_l1 {
+                break (probe, status);
+            }
+            hi = probe;
+            step = step.saturating_mul(2);
+        };
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            let (status, on_l1) = self.block_finality(BlockId::Number(mid)).await?;
+            if on_l1 {
+                lo = mid;
+                best = status;
+            } else {
+                hi = mid;
+            }
         }
+        Ok(best)
+    }
 
-        // Default to safe limit: 5 requests per second (typical free tier)
-        // Note: This limit is global for the client struct, effectively limiting total throughput 
-        // regardless of which provider is used next.
-        let limiter = ApiRateLimiter::new(5)?;
+    /// `block_status`, plus whether the block is `ACCEPTED_ON_L1`.
+    async fn block_finality(&self, block_id: BlockId) -> Result<(NetworkStatus, bool)> {
+        use starknet::core::types::{BlockStatus, MaybePendingBlockWithTxHashes};
 
-        Ok(StarknetClient { 
-            providers, 
-            current_index: AtomicUsize::new(0),
-            limiter 
+        let (idx, block) = self.request_on(|p| async move {
+            p.get_block_with_tx_hashes(block_id).await
+        }).await
+            .map_err(|e| rpc_failure("Failed to fetch block", e))?;
+
+        // Gas prices are FieldElements in this version.
+        // Convert via string to avoid trait complexity (Felt -> u128)
+        let to_u128 = |felt: FieldElement| -> u128 { format!("{}", felt).parse().unwrap_or(0) };
+
+        match block {
+            MaybePendingBlockWithTxHashes::Block(b) => {
+                self.providers[idx].observe_head(b.block_number);
+                let status = NetworkStatus {
+                    block_number: b.block_number,
+                    l1_gas_price_wei: to_u128(b.l1_gas_price.price_in_wei),
+                    l1_gas_price_fri: to_u128(b.l1_gas_price.price_in_fri),
+                    is_pending: false,
+                };
+                Ok((status, b.status == BlockStatus::AcceptedOnL1))
+            }
+            MaybePendingBlockWithTxHashes::PendingBlock(b) => Ok((NetworkStatus {
+                block_number: 0,
+                l1_gas_price_wei: to_u128(b.l1_gas_price.price_in_wei),
+                l1_gas_price_fri: to_u128(b.l1_gas_price.price_in_fri),
+                is_pending: true,
+            }, false)),
+        }
+    }
+
+    /// `(block_number, l1_gas_price_wei)` for the latest `blocks` blocks, oldest
+    /// first. Blocks below the head are fetched concurrently. Feed the result to
+    /// [`crate::types::suggested_gas_price`] for a fee bound.
+    pub async fn gas_price_history(&self, blocks: u32) -> Result<Vec<(u64, u128)>> {
+        if blocks == 0 {
+            return Ok(Vec::new());
+        }
+        let head = self.get_network_status().await?;
+        let first = head.block_number.saturating_sub(blocks as u64 - 1);
+
+        let reads = (first..head.block_number).map(|number| self.block_status(BlockId::Number(number)));
+        let mut history: Vec<(u64, u128)> = futures::future::try_join_all(reads).await?
+            .into_iter()
+            .map(|status| (status.block_number, status.l1_gas_price_wei))
+            .collect();
+        history.push((head.block_number, head.l1_gas_price_wei));
+        Ok(history)
+    }
+
+    /// Every transaction in block `id`, decoded. `BlockId::Tag(BlockTag::Pending)`
+    /// reads the block still being built, whose contents may change.
+    pub async fn get_block_transactions(&self, id: BlockId) -> Result<Vec<BlockTransaction>> {
+        use starknet::core::types::MaybePendingBlockWithTxs;
+
+        let block = self.request(|p| async move { p.get_block_with_txs(id).await }).await
+            .map_err(|e| rpc_failure("Failed to fetch block transactions", e))?;
+
+        let transactions = match block {
+            MaybePendingBlockWithTxs::Block(b) => b.transactions,
+            MaybePendingBlockWithTxs::PendingBlock(b) => b.transactions,
+        };
+        Ok(transactions.into_iter().map(BlockTransaction::from).collect())
+    }
+
+    /// Number of transactions in block `id`, without fetching them. Cheaper than
+    /// `get_block_transactions` when only activity levels matter.
+    pub async fn get_block_transaction_count(&self, id: BlockId) -> Result<u64> {
+        self.request(|p| async move { p.get_block_transaction_count(id).await }).await
+            .map_err(|e| rpc_failure("Failed to fetch block transaction count", e))
+    }
+
+    /// Each new accepted block after the current head, found by polling
+    /// `get_network_status` every `block_poll_interval` (± `block_poll_jitter`). If several blocks land
+    /// between polls only the newest is yielded. Pending blocks, which
+    /// `get_network_status` reports as number 0, are never yielded or used as the
+    /// baseline. Failed polls are yielded as errors and polling continues; the
+    /// stream ends once the client is closed.
+    pub fn block_stream(&self) -> impl Stream<Item = Result<NetworkStatus>> + '_ {
+        futures::stream::unfold((None::<u64>, false), move |(mut last, mut polled)| async move {
+            loop {
+                if polled {
+                    tokio::time::sleep(self.next_poll_delay()).await;
+                }
+                polled = true;
+                if self.is_closed() {
+                    return None;
+                }
+                match self.get_network_status().await {
+                    Err(e) => return Some((Err(e), (last, polled))),
+                    // Number 0 here means "no number yet", not genesis.
+                    Ok(status) if status.is_pending => {}
+                    Ok(status) => match last {
+                        Some(prev) if status.block_number > prev => {
+                            last = Some(status.block_number);
+                            return Some((Ok(status), (last, polled)));
+                        }
+                        Some(_) => {}
+                        // First observation is the baseline, not a new block.
+                        None => last = Some(status.block_number),
+                    },
+                }
+            }
         })
     }
 
-    fn detect_rpc_urls() -> Result<Vec<String>> {
-        let keys = [
-            "STARKNET_RPC_URL",
-            "STARKNET_MAINNET_URL",
-            "STARKNET_LAVA_URL",
-            "STARKNET_1RPC_URL",
-            "ALCHEMY_RPC_URL",
-            "INFURA_RPC_URL",
-            "QUICKNODE_ENDPOINT",
-        ];
+    /// The poll interval with this client's jitter applied.
+    fn next_poll_delay(&self) -> Duration {
+        use rand::Rng;
 
-        let mut urls = Vec::new();
-        for key in keys {
-            if let Ok(val) = env::var(key) {
-                let trimmed = val.trim();
-                if !trimmed.is_empty() {
-                    // Validate URL format before adding
-                    if Url::parse(trimmed).is_ok() {
-                        urls.push(trimmed.to_string());
+        if self.block_poll_jitter == 0.0 {
+            return self.block_poll_interval;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - self.block_poll_jitter..=1.0 + self.block_poll_jitter);
+        self.block_poll_interval.mul_f64(factor)
+    }
+
+    /// Up to `count` new blocks from `block_stream`, or fewer if `timeout` fires
+    /// first. Fails only if nothing was collected and a poll failed.
+    pub async fn collect_blocks(&self, count: usize, timeout: Duration) -> Result<Vec<NetworkStatus>> {
+        let mut blocks = Vec::with_capacity(count);
+        let mut last_error = None;
+        let stream = self.block_stream();
+        futures::pin_mut!(stream);
+
+        let _ = tokio::time::timeout(timeout, async {
+            while blocks.len() < count {
+                match stream.next().await {
+                    Some(Ok(block)) => blocks.push(block),
+                    Some(Err(e)) => {
+                        log::warn!("Block poll failed: {}", e);
+                        last_error = Some(e);
                     }
+                    None => break,
                 }
             }
-        }
-        
-        if urls.is_empty() {
-            Err(anyhow::anyhow!("No valid RPC URL found in environment variables."))
-        } else {
-            Ok(urls)
+        }).await;
+
+        match last_error {
+            Some(e) if blocks.is_empty() => Err(e),
+            _ => Ok(blocks),
         }
     }
 
-    fn next_provider(&self) -> &JsonRpcClient<HttpTransport> {
-        let idx = self.current_index.fetch_add(1, Ordering::Relaxed);
-        &self.providers[idx % self.providers.len()]
+    /// Address-taking reads accept anything [`IntoAddress`]: an [`Address`], a
+    /// `FieldElement`, or a hex string.
+    pub async fn get_eth_balance(&self, address: impl IntoAddress) -> Result<u128> {
+        self.get_eth_balance_at(address, None).await
     }
 
-    pub async fn get_network_status(&self) -> Result<(u64, u128)> {
-        self.limiter.check().await;
-        use starknet::core::types::{BlockId, BlockTag, MaybePendingBlockWithTxHashes};
+    /// `get_eth_balance` as a `Priority::Interactive` request, for reads a user
+    /// is waiting on (e.g. before confirming a purchase).
+    pub async fn get_eth_balance_priority(&self, address: impl IntoAddress) -> Result<u128> {
+        let token = self.eth_address()?;
+        let user_address = address.into_address()?;
+        let result = self.call_with_priority(token, "balanceOf", vec![user_address.felt()], None, Priority::Interactive).await
+            .map_err(|e| with_prefix("Failed to fetch balance", e))?;
+        balance_from_felts(&result)
+    }
 
-        let provider = self.next_provider();
-        
-        let block = provider.get_block_with_tx_hashes(BlockId::Tag(BlockTag::Latest)).await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch block: {}", e))?;
+    /// `get_eth_balance` against `block`; see `get_token_balance_at`.
+    pub async fn get_eth_balance_at(&self, address: impl IntoAddress, block: Option<BlockId>) -> Result<u128> {
+        self.get_token_balance_at(self.eth_address()?, address, block).await
+    }
 
-        match block {
-            MaybePendingBlockWithTxHashes::Block(b) => {
-                // l1_gas_price is FieldElement in this version.
-                // Convert via string to avoid trait complexity (Felt -> u128)
-                let gas_felt = b.l1_gas_price.price_in_wei; 
-                let gas: u128 = format!("{}", gas_felt).parse().unwrap_or(0);
-                Ok((b.block_number, gas))
-            },
-            MaybePendingBlockWithTxHashes::PendingBlock(b) => {
-                 let gas_felt = b.l1_gas_price.price_in_wei;
-                 let gas: u128 = format!("{}", gas_felt).parse().unwrap_or(0);
-                 Ok((0, gas))
+    /// The registered ETH contract, or [`ETH_CONTRACT`].
+    fn eth_address(&self) -> Result<Address> {
+        self.contract_address(ContractRegistry::ETH).unwrap_or(ETH_CONTRACT).parse()
+    }
+
+    /// ERC20 `balanceOf(address)` on `token`, at the latest block.
+    pub async fn get_token_balance(&self, token: impl IntoAddress, address: impl IntoAddress) -> Result<u128> {
+        self.get_token_balance_at(token, address, None).await
+    }
+
+    /// `get_token_balance` against `block`, defaulting to `Latest`. Pass
+    /// `BlockId::Tag(BlockTag::Pending)` for the freshest balance; pending state can
+    /// still change if its transactions are dropped or reordered before the block closes.
+    pub async fn get_token_balance_at(&self, token: impl IntoAddress, address: impl IntoAddress, block: Option<BlockId>) -> Result<u128> {
+        let token = token.into_address().context("Invalid contract address")?;
+        let result = self.get_token_balance_raw(token, address, block).await?;
+        balance_from_felts(&result)
+    }
+
+    /// The felts `balanceOf(address)` on `token` returned, undecoded, for debugging
+    /// balances that come back wrong.
+    pub async fn get_token_balance_raw(
+        &self,
+        token: impl IntoAddress,
+        address: impl IntoAddress,
+        block: Option<BlockId>,
+    ) -> Result<Vec<FieldElement>> {
+        let user_address = address.into_address()?;
+        self.call_at(token, "balanceOf", vec![user_address.felt()], block).await
+            .map_err(|e| with_prefix("Failed to fetch balance", e))
+    }
+
+    /// ERC20 `allowance(owner, spender)` on `token`: how much `spender` may still
+    /// transfer from `owner`. Unlike balances this returns the full Uint256, since
+    /// "unlimited" approvals are commonly `2^256 - 1`.
+    pub async fn get_allowance(&self, token: impl IntoAddress, owner: impl IntoAddress, spender: impl IntoAddress) -> Result<U256> {
+        let owner = owner.into_address().context("Invalid owner address")?;
+        let spender = spender.into_address().context("Invalid spender address")?;
+
+        let result = self.call(token, "allowance", vec![owner.felt(), spender.felt()]).await
+            .map_err(|e| with_prefix("Failed to fetch allowance", e))?;
+        decode_u256("allowance", &result)?.ok_or_else(|| unexpected_response("allowance", "expected 2 felts", &result))
+    }
+
+    /// Balances of `address` across several tokens, sent as one JSON-RPC batch (see
+    /// `call_many`). Each entry carries its own result, so one failing token
+    /// doesn't discard the rest.
+    pub async fn get_all_balances(&self, address: impl IntoAddress, tokens: &[&str]) -> Vec<(String, Result<u128>)> {
+        let user_address = match address.into_address() {
+            Ok(address) => address.felt(),
+            Err(e) => {
+                let msg = format!("Failed to fetch balance: {}", e);
+                return tokens.iter().map(|token| (token.to_string(), Err(anyhow::anyhow!(msg.clone())))).collect();
             }
-        }
+        };
+
+        let calls: Vec<(&str, &str, Vec<FieldElement>)> = tokens.iter()
+            .map(|token| (*token, "balanceOf", vec![user_address]))
+            .collect();
+        let results = self.call_many(&calls, None).await;
+
+        tokens.iter().zip(results).map(|(token, result)| {
+            let balance = result
+                .map_err(|e| with_prefix("Failed to fetch balance", e))
+                .and_then(|felts| balance_from_felts(&felts));
+            (token.to_string(), balance)
+        }).collect()
+    }
+
+    /// ERC721 `balanceOf(owner)` on `collection`: how many NFTs (e.g. asteroids)
+    /// `owner` holds. Expects the count as a single felt, as game collections
+    /// return it, not the Uint256 the ERC20 path decodes.
+    pub async fn get_nft_balance(&self, collection: impl IntoAddress, owner: impl IntoAddress) -> Result<u64> {
+        let owner = owner.into_address()?;
+        let result = self.call(collection, "balanceOf", vec![owner.felt()]).await
+            .map_err(|e| with_prefix("Failed to fetch NFT balance", e))?;
+        first_u64(&result).ok_or_else(|| unexpected_response("balanceOf", "expected a u64 count", &result))
+    }
+
+    /// Token ids held by `owner`, via ERC721Enumerable `tokenOfOwnerByIndex(owner, i)`
+    /// for each `i` below `get_nft_balance`. Index and id are Uint256s (`[low, high]`)
+    /// as in the OpenZeppelin ABI; ids above `u64::MAX` are refused. Fails on
+    /// collections without enumeration.
+    pub async fn get_owned_token_ids(&self, collection: impl IntoAddress, owner: impl IntoAddress) -> Result<Vec<u64>> {
+        let collection = collection.into_address().context("Invalid contract address")?;
+        let owner = owner.into_address()?;
+        let count = self.get_nft_balance(collection, owner).await?;
+
+        let reads = (0..count).map(|index| async move {
+            let calldata = vec![owner.felt(), FieldElement::from(index), FieldElement::ZERO];
+            let result = self.call(collection, "tokenOfOwnerByIndex", calldata).await?;
+            decode_u256("tokenOfOwnerByIndex", &result)?
+                .and_then(|id| id.to_u128())
+                .and_then(|id| u64::try_from(id).ok())
+                .ok_or_else(|| unexpected_response("tokenOfOwnerByIndex", "expected a u64 token id as 2 felts", &result))
+        });
+        futures::future::try_join_all(reads).await
+            .map_err(|e| with_prefix(format!("Failed to enumerate tokens of {}", owner), e))
+    }
+
+    /// ERC20 `name`, `symbol` and `decimals`. Handles both Cairo 0 short-string and
+    /// Cairo 1 `ByteArray` encodings of the string fields.
+    pub async fn get_token_metadata(&self, token: impl IntoAddress) -> Result<TokenMetadata> {
+        let token = token.into_address().context("Invalid contract address")?;
+        let name = self.call(token, "name", vec![]).await?;
+        let symbol = self.call(token, "symbol", vec![]).await?;
+        let decimals = self.call(token, "decimals", vec![]).await?;
+
+        let string = |entry_point: &str, felts: &[FieldElement]| {
+            decode_cairo_string(felts).map_err(|e| unexpected_response(entry_point, &e.to_string(), felts))
+        };
+        Ok(TokenMetadata {
+            name: string("name", name.as_slice())?,
+            symbol: string("symbol", symbol.as_slice())?,
+            decimals: first_u64(&decimals)
+                .and_then(|d| u8::try_from(d).ok())
+                .ok_or_else(|| unexpected_response("decimals", "expected a u8", &decimals))?,
+        })
+    }
+
+    /// Call a view function by name against the latest block and return the raw felts.
+    pub async fn call(&self, contract: impl IntoAddress, entry_point: &str, calldata: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        self.call_at(contract, entry_point, calldata, None).await
+    }
+
+    /// `call` against `block`, defaulting to `BlockPurpose::Read`.
+    pub async fn call_at(
+        &self,
+        contract: impl IntoAddress,
+        entry_point: &str,
+        calldata: Vec<FieldElement>,
+        block: Option<BlockId>,
+    ) -> Result<Vec<FieldElement>> {
+        self.call_with_priority(contract, entry_point, calldata, block, Priority::Background).await
+    }
+
+    /// `call_at` that fails with `ClientError::UnexpectedResponse` ("expected N
+    /// felts, got M") unless the result is exactly `expected_len` felts long.
+    /// `None` accepts any length, for variable-length returns.
+    pub async fn call_checked(
+        &self,
+        contract: impl IntoAddress,
+        entry_point: &str,
+        calldata: Vec<FieldElement>,
+        block: Option<BlockId>,
+        expected_len: Option<usize>,
+    ) -> Result<Vec<FieldElement>> {
+        self.call_inner(contract, entry_point, calldata, block, Priority::Background, expected_len).await
+    }
+
+    /// `call_at`, rate limited as `priority`.
+    pub async fn call_with_priority(
+        &self,
+        contract: impl IntoAddress,
+        entry_point: &str,
+        calldata: Vec<FieldElement>,
+        block: Option<BlockId>,
+        priority: Priority,
+    ) -> Result<Vec<FieldElement>> {
+        self.call_inner(contract, entry_point, calldata, block, priority, None).await
     }
 
-    pub async fn get_eth_balance(&self, address: &str) -> Result<u128> {
-        self.limiter.check().await;
-        use starknet::core::types::{BlockId, BlockTag, FunctionCall, FieldElement};
+    async fn call_inner(
+        &self,
+        contract: impl IntoAddress,
+        entry_point: &str,
+        calldata: Vec<FieldElement>,
+        block: Option<BlockId>,
+        priority: Priority,
+        expected_len: Option<usize>,
+    ) -> Result<Vec<FieldElement>> {
+        use starknet::core::types::FunctionCall;
         use starknet::core::utils::get_selector_from_name;
-        
-        let provider = self.next_provider();
-        let eth_contract = FieldElement::from_hex_be("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7")?;
-        let selector = get_selector_from_name("balanceOf")?;
-        let user_address = FieldElement::from_hex_be(address).context("Invalid address format")?;
 
         let call = FunctionCall {
-            contract_address: eth_contract,
-            entry_point_selector: selector,
-            calldata: vec![user_address],
+            contract_address: contract.into_address().context("Invalid contract address")?.felt(),
+            entry_point_selector: get_selector_from_name(entry_point)?,
+            calldata,
         };
 
-        let result = provider.call(call, BlockId::Tag(BlockTag::Latest)).await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch balance: {}", e))?;
-            
-        // Uint256 is [low, high]
-        if result.len() < 2 {
-            return Ok(0);
+        let block_id = BlockPurpose::Read.resolve(block);
+        let (_, result) = self.request_via(priority, ProviderSlot::client, |p| {
+            let call = call.clone();
+            async move { p.call(call, block_id).await }
+        }).await
+            .map_err(|e| rpc_failure(format!("Call to {} failed", entry_point), e))?;
+
+        match expected_len {
+            Some(expected) if result.len() != expected => Err(unexpected_response(
+                entry_point,
+                &format!("expected {} felts, got {}", expected, result.len()),
+                &result,
+            )),
+            _ => Ok(result),
         }
-        
-        // Convert low part to u128. High part ignored (safe for < 3.4 * 10^38 Wei)
-        let low = result[0];
-        let balance: u128 = format!("{}", low).parse().unwrap_or(0);
-        
-        Ok(balance)
     }
 
-    pub async fn get_nonce(&self, address: &str) -> Result<String> {
-        self.limiter.check().await;
-        use starknet::core::types::{BlockId, BlockTag, FieldElement};
-        
-        let provider = self.next_provider();
-        let user_address = FieldElement::from_hex_be(address).context("Invalid address format")?;
+    /// Several view calls of `(contract, entry_point, calldata)` against `block`,
+    /// sent as JSON-RPC batches of up to `MAX_BATCH_SIZE` calls per HTTP request.
+    /// Results come back in `calls` order, each on its own. If a provider rejects
+    /// batches, the calls are retried as concurrent individual requests.
+    pub async fn call_many(
+        &self,
+        calls: &[(&str, &str, Vec<FieldElement>)],
+        block: Option<BlockId>,
+    ) -> Vec<Result<Vec<FieldElement>>> {
+        use futures::future::join_all;
+
+        let chunks = calls.chunks(MAX_BATCH_SIZE).map(|chunk| self.call_batch(chunk, block));
+        join_all(chunks).await.into_iter().flatten().collect()
+    }
+
+    /// One batch of `call_many`.
+    async fn call_batch(
+        &self,
+        calls: &[(&str, &str, Vec<FieldElement>)],
+        block: Option<BlockId>,
+    ) -> Vec<Result<Vec<FieldElement>>> {
+        use futures::future::join_all;
+        use starknet::core::types::FunctionCall;
+        use starknet::core::utils::get_selector_from_name;
+        use starknet::providers::jsonrpc::JsonRpcMethod;
+        use transport::BatchCall;
+
+        let block_id = BlockPurpose::Read.resolve(block);
+        let mut results: Vec<Option<Result<Vec<FieldElement>>>> = Vec::with_capacity(calls.len());
+        // Calls that can't be built fail on their own, without going out.
+        let mut batch = Vec::new();
+        let mut positions = Vec::new();
+        for (i, (contract, entry_point, calldata)) in calls.iter().enumerate() {
+            let call = parse_address(contract).context("Invalid contract address").and_then(|contract_address| {
+                Ok(FunctionCall {
+                    contract_address,
+                    entry_point_selector: get_selector_from_name(entry_point)?,
+                    calldata: calldata.clone(),
+                })
+            });
+            match call.map(|call| serde_json::json!([call, block_id])) {
+                Ok(params) => {
+                    results.push(None);
+                    positions.push(i);
+                    batch.push(BatchCall { method: JsonRpcMethod::Call, params });
+                }
+                Err(e) => results.push(Some(Err(anyhow::anyhow!("Call to {} failed: {}", entry_point, e)))),
+            }
+        }
+        if batch.is_empty() {
+            return results.into_iter().flatten().collect();
+        }
+
+        let sent = self.request_via(Priority::Background, ProviderSlot::transport, |t| {
+            let batch = &batch;
+            async move {
+                t.send_batch(batch).await
+                    .map_err(|e| ProviderError::from(JsonRpcClientError::TransportError(e)))
+            }
+        }).await;
+
+        match sent {
+            Ok((_, responses)) => {
+                for (i, response) in positions.into_iter().zip(responses) {
+                    let entry_point = calls[i].1;
+                    results[i] = Some(
+                        response
+                            .map_err(|e| typed_failure(format!("Call to {} failed: {}", entry_point, e), Some(&e)))
+                            .and_then(|value| serde_json::from_value(value)
+                                .map_err(|e| anyhow::anyhow!("Call to {} failed: {}", entry_point, e))),
+                    );
+                }
+            }
+            Err(e) if self.is_closed() => {
+                for i in positions {
+                    results[i] = Some(Err(anyhow::anyhow!("Call to {} failed: {}", calls[i].1, e)));
+                }
+            }
+            Err(e) => {
+                log::debug!("JSON-RPC batch failed ({}), falling back to individual calls", e);
+                let singles = positions.iter().map(|&i| {
+                    let (contract, entry_point, calldata) = &calls[i];
+                    self.call_at(*contract, entry_point, calldata.clone(), block)
+                });
+                for (i, result) in positions.iter().zip(join_all(singles).await) {
+                    results[*i] = Some(result);
+                }
+            }
+        }
+        results.into_iter().map(|r| r.expect("every call answered")).collect()
+    }
+
+    /// Value of storage slot `key` of `contract` at `block` (default `BlockPurpose::Read`).
+    pub async fn get_storage_at(&self, contract: impl IntoAddress, key: FieldElement, block: Option<BlockId>) -> Result<FieldElement> {
+        let contract = contract.into_address().context("Invalid contract address")?;
+        let contract_address = contract.felt();
+        let block_id = BlockPurpose::Read.resolve(block);
+
+        self.request(|p| async move { p.get_storage_at(contract_address, key, block_id).await }).await
+            .map_err(|e| rpc_failure(format!("Failed to read storage {:#x} of {}", key, contract), e))
+    }
+
+    /// Several storage slots of `contract`, sent as JSON-RPC batches of up to
+    /// `MAX_BATCH_SIZE` reads. Results are aligned with `keys`, each on its own. If
+    /// a provider rejects batches, the slots are read concurrently instead.
+    pub async fn get_storage_multi(
+        &self,
+        contract: impl IntoAddress,
+        keys: &[FieldElement],
+        block: Option<BlockId>,
+    ) -> Vec<Result<FieldElement>> {
+        use futures::future::join_all;
+
+        let contract = match contract.into_address() {
+            Ok(contract) => contract,
+            Err(e) => {
+                let msg = format!("Invalid contract address: {}", e);
+                return keys.iter().map(|_| Err(anyhow::anyhow!(msg.clone()))).collect();
+            }
+        };
+        let chunks = keys.chunks(MAX_BATCH_SIZE).map(|chunk| self.storage_batch(contract, chunk, block));
+        join_all(chunks).await.into_iter().flatten().collect()
+    }
+
+    /// One batch of `get_storage_multi`.
+    async fn storage_batch(&self, contract: Address, keys: &[FieldElement], block: Option<BlockId>) -> Vec<Result<FieldElement>> {
+        use futures::future::join_all;
+        use starknet::providers::jsonrpc::JsonRpcMethod;
+        use transport::BatchCall;
+
+        let block_id = BlockPurpose::Read.resolve(block);
+        let batch: Vec<BatchCall> = keys.iter()
+            .map(|key| BatchCall {
+                method: JsonRpcMethod::GetStorageAt,
+                // Plain `FieldElement` serializes as decimal; the spec wants hex.
+                params: serde_json::json!([format!("{:#x}", contract.felt()), format!("{:#x}", key), block_id]),
+            })
+            .collect();
+        let failure = |key: &FieldElement| format!("Failed to read storage {:#x} of {}", key, contract);
+
+        let sent = self.request_via(Priority::Background, ProviderSlot::transport, |t| {
+            let batch = &batch;
+            async move {
+                t.send_batch(batch).await
+                    .map_err(|e| ProviderError::from(JsonRpcClientError::TransportError(e)))
+            }
+        }).await;
+
+        match sent {
+            Ok((_, responses)) => keys.iter().zip(responses)
+                .map(|(key, response)| {
+                    response
+                        .map_err(|e| typed_failure(format!("{}: {}", failure(key), e), Some(&e)))
+                        .and_then(|value| serde_json::from_value(value)
+                            .map_err(|e| anyhow::anyhow!("{}: {}", failure(key), e)))
+                })
+                .collect(),
+            Err(e) if self.is_closed() => keys.iter().map(|key| Err(anyhow::anyhow!("{}: {}", failure(key), e))).collect(),
+            Err(e) => {
+                log::debug!("JSON-RPC batch failed ({}), falling back to individual storage reads", e);
+                join_all(keys.iter().map(|&key| self.get_storage_at(contract, key, block))).await
+            }
+        }
+    }
+
+    /// Class hash of the contract deployed at `contract`, at `block` (default `Latest`).
+    pub async fn get_class_hash_at(&self, contract: impl IntoAddress, block: Option<BlockId>) -> Result<FieldElement> {
+        let contract = contract.into_address().context("Invalid contract address")?;
+        let contract_address = contract.felt();
+        let block_id = BlockPurpose::Read.resolve(block);
+
+        self.request(|p| async move { p.get_class_hash_at(block_id, contract_address).await }).await
+            .map_err(|e| rpc_failure(format!("Failed to fetch class hash of {}", contract), e))
+    }
 
-        let nonce = provider.get_nonce(BlockId::Tag(BlockTag::Latest), user_address).await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch nonce: {}", e))?;
+    /// Class definition (Sierra or legacy Cairo 0), including its ABI, at the latest block.
+    pub async fn get_class(&self, class_hash: FieldElement) -> Result<ContractClass> {
+        let block_id = BlockPurpose::Read.default_block();
+
+        self.request(|p| async move { p.get_class(block_id, class_hash).await }).await
+            .map_err(|e| rpc_failure(format!("Failed to fetch class {:#x}", class_hash), e))
+    }
+
+    /// Account nonce at the pending block, i.e. the nonce the next transaction must use.
+    pub async fn get_nonce(&self, address: impl IntoAddress) -> Result<String> {
+        self.get_nonce_at(address, None).await
+    }
+
+    /// `get_nonce` against `block`, defaulting to `BlockPurpose::Nonce`.
+    pub async fn get_nonce_at(&self, address: impl IntoAddress, block: Option<BlockId>) -> Result<String> {
+        let user_address = address.into_address()?.felt();
+
+        let block_id = BlockPurpose::Nonce.resolve(block);
+        let nonce = self.request(|p| async move {
+            p.get_nonce(block_id, user_address).await
+        }).await
+            .map_err(|e| rpc_failure("Failed to fetch nonce", e))?;
             
         Ok(format!("{}", nonce))
     }
 
+    /// Chain id to sign for. A built-in network set on the builder is trusted as
+    /// is; otherwise the node is asked once. A node that isn't on the default
+    /// (mainnet) network is an error, since contracts would resolve from the
+    /// wrong registry.
+    async fn chain_id(&self) -> Result<FieldElement> {
+        let built_in = self.config.network.chain_id();
+        if let (true, Some(id)) = (self.network_explicit, built_in) {
+            return Ok(cairo_short_string_to_felt(id)?);
+        }
+        let reported = *self.chain_id.get_or_try_init(|| async {
+            self.request(|p| async move { p.chain_id().await }).await
+                .map_err(|e| rpc_failure("Failed to fetch chain id", e))
+        }).await?;
+        if let (false, Some(id)) = (self.network_explicit, built_in) {
+            if cairo_short_string_to_felt(id)? != reported {
+                let name = parse_cairo_short_string(&reported).unwrap_or_else(|_| format!("{:#x}", reported));
+                return Err(anyhow::anyhow!(
+                    "Node is on chain {} but the client defaults to {}; set the network on the builder",
+                    name, id
+                ));
+            }
+        }
+        Ok(reported)
+    }
+
+    /// Estimate the fee of a signed (usually query-only) transaction, against
+    /// `block` (default `Pending`). Use [`FeeEstimate::resource_bounds`] or
+    /// [`FeeEstimate::max_fee`] to turn it into limits for the real transaction.
+    pub async fn estimate_fee(&self, tx: BroadcastedTransaction, block: Option<BlockId>) -> Result<FeeEstimate> {
+        let block_id = BlockPurpose::FeeEstimate.resolve(block);
+        let estimate = self.request(|p| {
+            let tx = tx.clone();
+            async move { p.estimate_fee_single(tx, [], block_id).await }
+        }).await
+            .map_err(|e| rpc_failure("Fee estimation failed", e))?;
+        FeeEstimate::try_from(estimate)
+    }
+
+    /// Submit `calls` from `account` as an invoke (v1) transaction signed by
+    /// `session`, returning the transaction hash.
+    ///
+    /// Expiry (against the builder's `clock`) and the session's policy are checked
+    /// before anything is signed. The signature is the plain `[r, s]` pair over the
+    /// transaction hash, so `account` must accept the session key as a signer. The
+    /// fee is estimated against the pending block and padded by 10%.
+    pub async fn execute_with_session(&self, session: &SessionKey, account: &str, calls: Vec<Call>) -> Result<FieldElement> {
+        use starknet::core::types::{BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV1};
+
+        if session.is_expired_on(self.clock.as_ref()) {
+            return Err(ClientError::SessionExpired { expires_at: session.expires_at }.into());
+        }
+        let policy = self.resolve_policy(&session.policy);
+        for call in &calls {
+            if !policy.permits(call.to, call.selector)? {
+                return Err(ClientError::CallNotPermitted {
+                    contract: felt_to_address_string(&call.to),
+                    selector: format!("{:#064x}", call.selector),
+                }.into());
+            }
+        }
+
+        let sender_address = parse_address(account).context("Invalid account address")?;
+        let chain_id = self.chain_id().await?;
+        let nonce_block = BlockPurpose::Nonce.default_block();
+        let nonce = self.request(|p| async move { p.get_nonce(nonce_block, sender_address).await }).await
+            .map_err(|e| rpc_failure("Failed to fetch nonce", e))?;
+        let calldata = encode_calls(&calls);
+
+        let signed = |max_fee: FieldElement, is_query: bool| -> Result<BroadcastedInvokeTransactionV1> {
+            let hash = invoke_v1_hash(sender_address, &calldata, max_fee, chain_id, nonce, is_query)?;
+            let signature = session.sign(hash)?;
+            Ok(BroadcastedInvokeTransactionV1 {
+                sender_address,
+                calldata: calldata.clone(),
+                max_fee,
+                signature: vec![signature.r, signature.s],
+                nonce,
+                is_query,
+            })
+        };
+
+        let query = BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(signed(FieldElement::ZERO, true)?));
+        let estimate = self.estimate_fee(query, None).await?;
+        let max_fee = FieldElement::from(estimate.max_fee(SESSION_FEE_MULTIPLIER));
+
+        let invoke = BroadcastedInvokeTransaction::V1(signed(max_fee, false)?);
+        let result = self.request(|p| {
+            let invoke = invoke.clone();
+            async move { p.add_invoke_transaction(invoke).await }
+        }).await
+            .map_err(|e| rpc_failure("Failed to submit transaction", e))?;
+
+        Ok(result.transaction_hash)
+    }
+
+    /// `policy` with contracts named by registry entry (e.g. `"interact"`) replaced by
+    /// their addresses. Unregistered names are kept and fail to parse in `permits`.
+    fn resolve_policy(&self, policy: &SessionPolicy) -> SessionPolicy {
+        let allowed_contracts = policy.allowed_contracts.iter()
+            .map(|contract| self.contract_address(contract).unwrap_or(contract).to_string())
+            .collect();
+        SessionPolicy { allowed_contracts, ..policy.clone() }
+    }
+
+    /// Current status of `tx_hash`: None while the node doesn't know it yet.
+    /// Rejected and reverted transactions are errors
+    /// ([`ClientError::TransactionRejected`], [`ClientError::TransactionReverted`]).
+    pub async fn get_transaction_status(&self, tx_hash: FieldElement) -> Result<Option<TxStatus>> {
+        use starknet::core::types::{TransactionExecutionStatus, TransactionStatus};
+
+        let status = match self.request(|p| async move { p.get_transaction_status(tx_hash).await }).await {
+            Ok(status) => status,
+            Err(e) if transport::rpc_error(&e).is_some_and(|rpc| rpc.code == TXN_HASH_NOT_FOUND) => return Ok(None),
+            Err(e) => return Err(rpc_failure(format!("Failed to fetch status of {:#x}", tx_hash), e)),
+        };
+        let (status, execution) = match status {
+            TransactionStatus::Received => return Ok(Some(TxStatus::Received)),
+            TransactionStatus::Rejected => return Err(ClientError::TransactionRejected { hash: tx_hash }.into()),
+            TransactionStatus::AcceptedOnL2(execution) => (TxStatus::AcceptedOnL2, execution),
+            TransactionStatus::AcceptedOnL1(execution) => (TxStatus::AcceptedOnL1, execution),
+        };
+        match execution {
+            TransactionExecutionStatus::Succeeded => Ok(Some(status)),
+            TransactionExecutionStatus::Reverted => Err(ClientError::TransactionReverted { hash: tx_hash }.into()),
+        }
+    }
+
+    /// Poll `tx_hash` until it reaches `target` (usually `AcceptedOnL2`), calling
+    /// `on_status` with each new status as it's first seen so a UI can show
+    /// progress. Polls start `tx_poll_interval` apart and back off by doubling, up
+    /// to 8x. Fails on rejection or revert, or if `timeout` passes first.
+    pub async fn wait_for_acceptance(
+        &self,
+        tx_hash: FieldElement,
+        target: TxStatus,
+        timeout: Duration,
+        mut on_status: impl FnMut(TxStatus),
+    ) -> Result<TxStatus> {
+        let deadline = Instant::now() + timeout;
+        let max_delay = self.tx_poll_interval * TX_POLL_MAX_FACTOR;
+        let mut delay = self.tx_poll_interval;
+        let mut last = None;
+        loop {
+            if let Some(status) = self.get_transaction_status(tx_hash).await? {
+                if last != Some(status) {
+                    last = Some(status);
+                    on_status(status);
+                }
+                if status >= target {
+                    return Ok(status);
+                }
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(anyhow::anyhow!(
+                    "Timed out after {:?} waiting for {:#x} (last status: {})",
+                    timeout,
+                    tx_hash,
+                    last.map_or("not found".to_string(), |s| format!("{:?}", s))
+                ));
+            }
+            tokio::time::sleep(delay.min(deadline - now)).await;
+            delay = (delay * 2).min(max_delay);
+        }
+    }
+
     /// Fetch Crew Status (ADR-041)
     /// Returns: (is_busy, food_level)
     /// Currently MOCKED for Phase 4. Needs SAGE/Contract integration.
     pub async fn get_crew_status(&self, _crew_id: u64) -> Result<(bool, u8)> {
-        self.limiter.check().await;
         // logic to check chain/indexer
         // Mock: Always Ready, 100% Food
         let is_busy = false;
@@ -167,14 +1988,254 @@ impl StarknetClient {
         Ok((is_busy, food_level))
     }
 
-    /// Execute a batched query (Multicall).
-    pub async fn batch_query(&self, _account_address: &str, _asteroids: &[u64]) -> Result<String> {
-        self.limiter.check().await;
-        let _provider = self.next_provider();
-        
-        // logic to construct a Multicall transaction or multiple async queries
-        // For v0.1.0, we will simulate this.
-        
-        Ok("{\"balance\": \"1000 SWAY\", \"asteroids\": []}".to_string())
+    /// Read one asteroid from the configured asteroid contract via
+    /// `get_asteroid(id)`, which returns
+    /// `[owner, name, resource_count, (resource, abundance_bps) * resource_count]`
+    /// with names as short strings and abundances in basis points.
+    pub async fn get_asteroid(&self, asteroid_id: u64) -> Result<AsteroidInfo> {
+        use starknet::core::utils::parse_cairo_short_string;
+
+        let contract = self.contract_address(ContractRegistry::ASTEROIDS)
+            .context("No asteroid contract configured; none is seeded, so set one with StarknetClientBuilder::asteroid_contract")?;
+
+        let result = self.call(contract, "get_asteroid", vec![FieldElement::from(asteroid_id)]).await?;
+
+        let [owner, name, count, rest @ ..] = result.as_slice() else {
+            return Err(anyhow::anyhow!("get_asteroid returned {} felts, expected at least 3", result.len()));
+        };
+        let count: usize = format!("{}", count).parse().context("Invalid resource count")?;
+        // `count` comes from the node: check the arithmetic rather than trust it.
+        if count.checked_mul(2) != Some(rest.len()) {
+            let reason = format!("declared {} resources but returned {} felts for them", count, rest.len());
+            return Err(unexpected_response("get_asteroid", &reason, &result));
+        }
+
+        let mut resource_abundances = HashMap::new();
+        for pair in rest.chunks_exact(2) {
+            let bps: u64 = format!("{}", pair[1]).parse().context("Invalid resource abundance")?;
+            resource_abundances.insert(parse_cairo_short_string(&pair[0])?, bps as f64 / 10_000.0);
+        }
+
+        Ok(AsteroidInfo {
+            id: asteroid_id,
+            name: parse_cairo_short_string(name)?,
+            owner: felt_to_address_string(owner),
+            resource_abundances,
+        })
+    }
+
+    /// Canonical address behind a Starknet ID name such as `alice.stark` (the
+    /// suffix is optional), via the naming contract's `domain_to_address`.
+    /// Fails if the name doesn't resolve.
+    pub async fn resolve_starknet_id(&self, name: &str) -> Result<String> {
+        let contract = self.starknet_id_contract()?;
+        let domain = starknet_id::encode_domain(name)?;
+
+        let mut calldata = Calldata::new();
+        calldata.push_array(&domain).push_array(&[]);
+        let result = self.call_checked(contract, "domain_to_address", calldata.into_vec(), None, Some(1)).await?;
+        if result[0] == FieldElement::ZERO {
+            return Err(anyhow::anyhow!("Starknet ID {:?} does not resolve to an address", name));
+        }
+        Ok(felt_to_address_string(&result[0]))
+    }
+
+    /// Main Starknet ID name of `address` (e.g. `alice.stark`), via the naming
+    /// contract's `address_to_domain`. Fails if the address has none.
+    pub async fn address_to_name(&self, address: impl IntoAddress) -> Result<String> {
+        let contract = self.starknet_id_contract()?;
+        let address = address.into_address().context("Invalid address")?;
+
+        let mut calldata = Calldata::new();
+        calldata.push_felt(address.felt()).push_array(&[]);
+        let result = self.call(contract, "address_to_domain", calldata.into_vec()).await?;
+        let Some((len, labels)) = result.split_first() else {
+            return Err(unexpected_response("address_to_domain", "expected a felt array", &result));
+        };
+        if *len != FieldElement::from(labels.len()) {
+            return Err(unexpected_response("address_to_domain", "array length mismatch", &result));
+        }
+        if labels.is_empty() {
+            return Err(anyhow::anyhow!("No Starknet ID name set for {}", address));
+        }
+        starknet_id::decode_domain(labels)
+    }
+
+    fn starknet_id_contract(&self) -> Result<&str> {
+        self.contract_address(ContractRegistry::STARKNET_ID)
+            .context("No Starknet ID contract configured for this network (see StarknetClientBuilder::starknet_id_contract)")
+    }
+
+    /// Execute the reads `request` declares. Token balances go out as one
+    /// JSON-RPC batch (see `get_all_balances`); asteroids and metadata are read
+    /// concurrently. A read that fails is reported as an error entry. Fails only
+    /// if the account address is invalid.
+    pub async fn batch_query(&self, request: BatchQueryRequest) -> Result<BatchQueryResult> {
+        use futures::future::join_all;
+
+        let account = request.account.as_str().into_address().context("Invalid account address")?;
+        let tokens: Vec<&str> = request.tokens.iter()
+            .map(|token| self.contract_address(token).unwrap_or(token.as_str()))
+            .collect();
+
+        let include_metadata = request.include_metadata;
+        let balances = self.get_all_balances(account, &tokens);
+        let metadata = join_all(tokens.iter().map(|&token| async move {
+            if include_metadata { Some(self.get_token_metadata(token).await) } else { None }
+        }));
+        let asteroids = join_all(request.asteroids.iter().map(|&id| async move {
+            match self.get_asteroid(id).await {
+                Ok(info) => AsteroidEntry::Ok(info),
+                Err(e) => AsteroidEntry::Error { id, error: e.to_string() },
+            }
+        }));
+        let (balances, metadata, asteroids) = futures::join!(balances, metadata, asteroids);
+
+        let balances = request.tokens.iter().zip(balances).zip(metadata)
+            .map(|((token, (_, balance)), metadata)| {
+                let token = token.clone();
+                match (balance, metadata.transpose()) {
+                    (Ok(balance), Ok(metadata)) => BalanceEntry::Ok { token, balance: U256::from(balance), metadata },
+                    (Err(e), _) | (_, Err(e)) => BalanceEntry::Error { token, error: e.to_string() },
+                }
+            })
+            .collect();
+        Ok(BatchQueryResult { balances, asteroids })
+    }
+
+    /// `batch_query` for the common case: `account`'s ETH balance plus `asteroids`.
+    pub async fn batch_query_account(&self, account: &str, asteroids: &[u64]) -> Result<BatchQueryResult> {
+        self.batch_query(BatchQueryRequest {
+            account: account.to_string(),
+            tokens: vec![ContractRegistry::ETH.to_string()],
+            asteroids: asteroids.to_vec(),
+            include_metadata: false,
+        }).await
+    }
+}
+
+/// Loopback endpoints, i.e. a node running on this machine.
+fn is_local_url(url: &Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(host)) => host.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// `__execute__` calldata for Cairo 1 accounts: the call count, then each call as
+/// `to, selector, calldata_len, calldata...`.
+fn encode_calls(calls: &[Call]) -> Vec<FieldElement> {
+    let mut encoded = vec![FieldElement::from(calls.len())];
+    for call in calls {
+        encoded.extend([call.to, call.selector, FieldElement::from(call.calldata.len())]);
+        encoded.extend(&call.calldata);
+    }
+    encoded
+}
+
+/// Invoke v1 transaction hash. Query-only transactions use version `2^128 + 1`,
+/// so their signatures can't be replayed as real transactions.
+fn invoke_v1_hash(
+    sender: FieldElement,
+    calldata: &[FieldElement],
+    max_fee: FieldElement,
+    chain_id: FieldElement,
+    nonce: FieldElement,
+    is_query: bool,
+) -> Result<FieldElement> {
+    let version = if is_query {
+        FieldElement::from_hex_be("0x100000000000000000000000000000001")?
+    } else {
+        FieldElement::ONE
+    };
+    Ok(compute_hash_on_elements(&[
+        cairo_short_string_to_felt("invoke")?,
+        version,
+        sender,
+        FieldElement::ZERO, // entry_point_selector
+        compute_hash_on_elements(calldata),
+        max_fee,
+        chain_id,
+        nonce,
+    ]))
+}
+
+/// A Uint256 return value of `entry_point`: `[low, high]`. None if fewer than two
+/// felts; an error if either limb is >= 2^128, which the Uint256 ABI rules out.
+fn decode_u256(entry_point: &str, felts: &[FieldElement]) -> Result<Option<U256>> {
+    let [low, high, ..] = felts else {
+        return Ok(None);
+    };
+    let limb = |felt: &FieldElement| -> Result<u128> {
+        format!("{}", felt).parse()
+            .map_err(|_| unexpected_response(entry_point, &format!("Uint256 limb {:#x} is not below 2^128", felt), felts))
+    };
+    Ok(Some(U256::new(limb(low)?, limb(high)?)))
+}
+
+/// ERC20 `balanceOf` result as a u128. Short responses are an `UnexpectedResponse`;
+/// balances >= 2^128 are refused rather than truncated.
+fn balance_from_felts(felts: &[FieldElement]) -> Result<u128> {
+    let balance = decode_u256("balanceOf", felts)?.ok_or_else(|| unexpected_response("balanceOf", "expected 2 felts", felts))?;
+    balance.to_u128().ok_or_else(|| ClientError::BalanceOverflow { balance }.into())
+}
+
+/// `err` as an `anyhow` error reading "`prefix`: `err`". JSON-RPC errors with a
+/// Starknet code `ClientError::from_rpc` knows stay downcastable to `ClientError`.
+fn rpc_failure(prefix: impl fmt::Display, err: ProviderError) -> anyhow::Error {
+    typed_failure(format!("{}: {}", prefix, err), transport::rpc_error(&err))
+}
+
+/// `message`, carrying the `ClientError` for `rpc` if it maps to one.
+fn typed_failure(message: String, rpc: Option<&RpcError>) -> anyhow::Error {
+    match rpc.and_then(ClientError::from_rpc) {
+        Some(typed) => anyhow::Error::new(typed).context(message),
+        None => anyhow::anyhow!(message),
+    }
+}
+
+/// Prefix `err`'s message without hiding what it wraps from `downcast_ref`.
+fn with_prefix(prefix: impl fmt::Display, err: anyhow::Error) -> anyhow::Error {
+    let message = format!("{}: {}", prefix, err);
+    err.context(message)
+}
+
+/// The first felt as a u64, if there is one and it fits.
+fn first_u64(felts: &[FieldElement]) -> Option<u64> {
+    format!("{}", felts.first()?).parse().ok()
+}
+
+fn unexpected_response(entry_point: &str, reason: &str, raw: &[FieldElement]) -> anyhow::Error {
+    ClientError::UnexpectedResponse {
+        entry_point: entry_point.to_string(),
+        reason: reason.to_string(),
+        raw: raw.to_vec(),
+    }
+    .into()
+}
+
+/// Decode a string returned by a view function: a single short-string felt
+/// (Cairo 0) or a serialized `ByteArray` (Cairo 1: word count, 31-byte words,
+/// pending word, pending length).
+fn decode_cairo_string(felts: &[FieldElement]) -> Result<String> {
+    use starknet::core::utils::parse_cairo_short_string;
+
+    match felts {
+        [] => Err(anyhow::anyhow!("Empty string response")),
+        [single] => Ok(parse_cairo_short_string(single)?),
+        [count, rest @ ..] => {
+            let count: usize = format!("{}", count).parse().context("Invalid ByteArray length")?;
+            if count.checked_add(2) != Some(rest.len()) {
+                return Err(anyhow::anyhow!("Malformed ByteArray: {} words declared, got {} felts", count, felts.len()));
+            }
+            let mut out = String::new();
+            for word in &rest[..count] {
+                out.push_str(&parse_cairo_short_string(word)?);
+            }
+            out.push_str(&parse_cairo_short_string(&rest[count])?);
+            Ok(out)
+        }
     }
 }
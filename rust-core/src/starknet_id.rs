@@ -0,0 +1,79 @@
+//! Starknet ID domain encoding, as used by the naming contract.
+//!
+//! Each label of `sub.name.stark` is one felt: its characters as base-38 digits,
+//! least significant first, over the alphabet `a-z0-9-`. A trailing `a` (digit 0)
+//! would vanish, so it's written as the escape digit 37 instead. Labels using
+//! Starknet ID's extended alphabet aren't supported.
+
+use anyhow::Result;
+use starknet::core::types::FieldElement;
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-";
+const BASE: u8 = ALPHABET.len() as u8 + 1;
+const ESCAPE: u8 = ALPHABET.len() as u8;
+/// Longest label whose encoding always fits in a felt.
+const MAX_LABEL_LEN: usize = 46;
+
+/// Encode `name` (with or without the `.stark` suffix) into one felt per label,
+/// in the order they're written.
+pub fn encode_domain(name: &str) -> Result<Vec<FieldElement>> {
+    let name = name.trim().to_ascii_lowercase();
+    let labels = name.strip_suffix(".stark").unwrap_or(&name);
+    labels.split('.').map(encode_label).collect()
+}
+
+/// Decode the naming contract's labels back into `name.stark`.
+pub fn decode_domain(labels: &[FieldElement]) -> Result<String> {
+    if labels.is_empty() {
+        return Err(anyhow::anyhow!("Empty Starknet ID domain"));
+    }
+    let labels: Vec<String> = labels.iter().map(decode_label).collect::<Result<_>>()?;
+    Ok(format!("{}.stark", labels.join(".")))
+}
+
+fn encode_label(label: &str) -> Result<FieldElement> {
+    if label.is_empty() || label.len() > MAX_LABEL_LEN {
+        return Err(anyhow::anyhow!("Starknet ID label {:?} must be 1 to {} characters", label, MAX_LABEL_LEN));
+    }
+    let mut digits = Vec::with_capacity(label.len());
+    for c in label.bytes() {
+        let digit = ALPHABET.iter().position(|&a| a == c)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported character {:?} in Starknet ID label {:?}", c as char, label))?;
+        digits.push(digit as u8);
+    }
+    if digits.last() == Some(&0) {
+        *digits.last_mut().unwrap() = ESCAPE;
+    }
+
+    let mut value = [0u8; 32];
+    for &digit in digits.iter().rev() {
+        let mut carry = digit as u32;
+        for byte in value.iter_mut().rev() {
+            let next = *byte as u32 * BASE as u32 + carry;
+            *byte = next as u8;
+            carry = next >> 8;
+        }
+    }
+    FieldElement::from_bytes_be(&value).map_err(|_| anyhow::anyhow!("Starknet ID label {:?} does not fit in a felt", label))
+}
+
+fn decode_label(felt: &FieldElement) -> Result<String> {
+    let mut value = felt.to_bytes_be();
+    let mut label = String::new();
+    while value.iter().any(|&b| b != 0) {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let acc = (remainder << 8) | *byte as u32;
+            *byte = (acc / BASE as u32) as u8;
+            remainder = acc % BASE as u32;
+        }
+        let digit = remainder as u8;
+        let rest_is_zero = value.iter().all(|&b| b == 0);
+        match digit {
+            ESCAPE if rest_is_zero => label.push('a'),
+            ESCAPE => return Err(anyhow::anyhow!("Starknet ID label {:#x} uses the extended alphabet", felt)),
+            d => label.push(ALPHABET[d as usize] as char),
+        }
+    }
+    Ok(label)
+}
@@ -1,5 +1,5 @@
-use std::collections::{HashMap, HashSet};
-use anyhow::{Result, Context};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,19 +8,231 @@ pub struct Recipe {
     pub outputs: HashMap<String, u32>,
     pub process_time_seconds: u32,
     pub energy_cost_kw: u32, 
+    /// Player's favoured route: wins ties against equally good recipes for the
+    /// same output, in path listings and in the recipe planners pick.
+    #[serde(default)]
+    pub preferred: bool,
+    /// Building type that runs this recipe (e.g. `"Refinery"`), for
+    /// `SupplyChainGraph::fits_within`. `None` counts as a building of its own.
+    #[serde(default)]
+    pub facility: Option<String>,
 }
 
+/// Recursion limit for planners walking the recipe graph.
+const MAX_PLAN_DEPTH: usize = 32;
+
+/// Version of the `ProductionPlan` JSON layout. Bump on any incompatible change
+/// to `ProductionPlan`, `ProductionNode` or `ProductionFeasibility`.
+pub const PLAN_SCHEMA_VERSION: u32 = 1;
+
+/// Answer to "can I build this from what I have?".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "missing", rename_all = "snake_case")]
+pub enum ProductionFeasibility {
+    Feasible,
+    /// Raw resource -> additional units needed.
+    Shortfall(HashMap<String, u32>),
+}
+
+/// Answer to "can my buildings sustain this output rate?".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "missing", rename_all = "snake_case")]
+pub enum FacilityFeasibility {
+    Feasible,
+    /// Facility type -> additional buildings needed.
+    Shortfall(HashMap<String, u32>),
+    /// The chain can't be planned (cyclic or too deep); carries the reason.
+    Unplannable(String),
+}
+
+/// One resource in a production tree. Raw resources have no recipe, zero runs
+/// and no inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductionNode {
+    pub resource: String,
+    pub quantity: u32,
+    pub recipe: Option<String>,
+    pub runs: u32,
+    /// Sorted by resource name so the serialized form is stable.
+    pub inputs: Vec<ProductionNode>,
+}
+
+/// What to build to get `quantity` of `target`, and the raw materials it takes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductionPlan {
+    pub schema_version: u32,
+    pub target: String,
+    pub quantity: u32,
+    pub root: ProductionNode,
+    pub raw_materials: BTreeMap<String, u32>,
+}
+
+/// Everything about building `quantity` of `target` in one value; see
+/// `SupplyChainGraph::plan_summary`. Maps are ordered so the JSON is stable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanSummary {
+    pub target: String,
+    pub quantity: u32,
+    /// Raw resources consumed, as in `raw_material_cost`.
+    pub raw_materials: BTreeMap<String, u32>,
+    /// Every resource flowing through the plan, as in `bill_of_materials`.
+    pub bill_of_materials: BTreeMap<String, u32>,
+    /// Distinct recipes in the plan, i.e. facilities if each step gets one.
+    pub facilities: usize,
+    /// Wall-clock seconds with one facility per step: each step's runs back to
+    /// back, after the slowest of its inputs.
+    pub critical_path_seconds: u64,
+    /// The recipe with the most total process time in the plan; None if the
+    /// target is raw.
+    pub bottleneck: Option<String>,
+}
+
+impl ProductionPlan {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a plan, rejecting layouts from other schema versions.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let plan: ProductionPlan = serde_json::from_str(json)?;
+        if plan.schema_version != PLAN_SCHEMA_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported plan schema version {} (expected {})",
+                plan.schema_version, PLAN_SCHEMA_VERSION
+            ));
+        }
+        Ok(plan)
+    }
+}
+
+/// Ordering for `find_production_path_by`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathOrder {
+    /// Shortest `process_time_seconds` first.
+    #[default]
+    Fastest,
+    /// Lowest input cost at the graph's resource prices first; recipes with
+    /// unpriced inputs go last.
+    Cheapest,
+}
+
+/// Score for `best_recipes_for`. Time and cost are per unit of the requested
+/// resource, so recipes with different batch sizes compare fairly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Seconds of process time per unit; lowest first.
+    Time,
+    /// Input cost per unit at the graph's resource prices; lowest first.
+    /// Recipes with unpriced inputs score infinity.
+    Cost,
+    /// Units produced per run; highest first.
+    Yield,
+}
+
+const CSV_HEADER: &str = "recipe,role,resource,quantity,process_time";
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Split CSV text into records with their starting line numbers. Line breaks
+/// inside quoted fields stay part of the record.
+fn csv_records(csv: &str) -> Vec<(usize, &str)> {
+    let mut records = Vec::new();
+    let (mut start, mut start_line, mut line_no) = (0, 1, 1);
+    let mut in_quotes = false;
+    for (i, c) in csv.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\n' => {
+                line_no += 1;
+                if !in_quotes {
+                    records.push((start_line, csv[start..i].trim_end_matches('\r')));
+                    (start, start_line) = (i + 1, line_no);
+                }
+            }
+            _ => {}
+        }
+    }
+    if start < csv.len() {
+        records.push((start_line, csv[start..].trim_end_matches('\r')));
+    }
+    records
+}
+
+/// Split one CSV record, honouring quoted fields. None on an unterminated quote.
+fn parse_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+/// Cloning copies recipes, the producer index and prices together, so a clone
+/// can be mutated for what-if planning without touching the original.
+#[derive(Debug, Clone)]
 pub struct SupplyChainGraph {
     recipes: HashMap<String, Recipe>,
     adjacency_list: HashMap<String, Vec<String>>, // Product -> Recipes that produce it
+    resource_prices: HashMap<String, f64>, // Optional market value per unit
+    raw_resources: BTreeSet<String>, // Declared raw materials (mined, not crafted)
+    version: Option<String>, // Game data release the recipes came from, if known
+}
+
+/// A data-quality finding from [`SupplyChainGraph::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphLint {
+    /// Used as an input but neither produced by any recipe nor declared raw.
+    /// Often a typo of a produced resource's name.
+    UnknownInput { resource: String, used_by: Vec<String> },
+    /// Names that differ only in case, whitespace, `-` or `_`.
+    NearDuplicateNames { names: Vec<String> },
+    EmptyInputs { recipe: String },
+    EmptyOutputs { recipe: String },
+}
+
+impl Default for SupplyChainGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only the given recipes: unlike `new`, the built-in steel recipe isn't added.
+impl FromIterator<(String, Recipe)> for SupplyChainGraph {
+    fn from_iter<I: IntoIterator<Item = (String, Recipe)>>(iter: I) -> Self {
+        let mut graph = Self::empty();
+        for (name, recipe) in iter {
+            graph.add_recipe(&name, recipe);
+        }
+        graph
+    }
 }
 
 impl SupplyChainGraph {
+    /// Graph seeded with the built-in "Refine Steel" recipe.
     pub fn new() -> Self {
-        let mut graph = SupplyChainGraph {
-            recipes: HashMap::new(),
-            adjacency_list: HashMap::new(),
-        };
+        let mut graph = Self::empty();
 
         // Hardcode "Iron -> Steel" Recipe (ADR-028)
         let mut inputs = HashMap::new();
@@ -35,25 +247,836 @@ impl SupplyChainGraph {
             outputs,
             process_time_seconds: 10, // Placeholder
             energy_cost_kw: 480, // ADR-024
+            preferred: false,
+            facility: Some("Refinery".to_string()),
         };
 
         graph.add_recipe("Refine Steel", recipe);
+        graph.mark_raw("Iron Ore");
+        graph.mark_raw("Fuel");
 
         graph
     }
 
+    fn empty() -> Self {
+        SupplyChainGraph {
+            recipes: HashMap::new(),
+            adjacency_list: HashMap::new(),
+            resource_prices: HashMap::new(),
+            raw_resources: BTreeSet::new(),
+            version: None,
+        }
+    }
+
+    /// Declare `resource` a raw material, so `lint` doesn't report it as unknown.
+    pub fn mark_raw(&mut self, resource: &str) {
+        self.raw_resources.insert(resource.to_string());
+    }
+
+    /// Flag likely data-entry mistakes. Findings are sorted for stable output.
+    pub fn lint(&self) -> Vec<GraphLint> {
+        let mut lints = Vec::new();
+
+        let mut names: Vec<&String> = self.recipes.keys().collect();
+        names.sort();
+        for name in &names {
+            let recipe = &self.recipes[*name];
+            if recipe.inputs.is_empty() {
+                lints.push(GraphLint::EmptyInputs { recipe: name.to_string() });
+            }
+            if recipe.outputs.is_empty() {
+                lints.push(GraphLint::EmptyOutputs { recipe: name.to_string() });
+            }
+        }
+
+        let mut unknown: BTreeMap<&String, Vec<String>> = BTreeMap::new();
+        for name in &names {
+            for input in self.recipes[*name].inputs.keys() {
+                if !self.produces(input) && !self.raw_resources.contains(input) {
+                    unknown.entry(input).or_default().push(name.to_string());
+                }
+            }
+        }
+        for (resource, used_by) in unknown {
+            lints.push(GraphLint::UnknownInput { resource: resource.clone(), used_by });
+        }
+
+        let mut by_key: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for resource in self.all_resources().into_iter().chain(self.raw_resources.iter().cloned()) {
+            let key: String = resource.chars()
+                .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+                .flat_map(char::to_lowercase)
+                .collect();
+            by_key.entry(key).or_default().insert(resource);
+        }
+        for names in by_key.into_values().filter(|names| names.len() > 1) {
+            lints.push(GraphLint::NearDuplicateNames { names: names.into_iter().collect() });
+        }
+
+        lints
+    }
+
+    /// Add a recipe, replacing any existing recipe of the same name.
     pub fn add_recipe(&mut self, name: &str, recipe: Recipe) {
-        self.recipes.insert(name.to_string(), recipe.clone());
+        self.remove_recipe(name);
+        for output in recipe.outputs.keys() {
+            self.adjacency_list.entry(output.clone()).or_default().push(name.to_string());
+        }
+        self.recipes.insert(name.to_string(), recipe);
+    }
+
+    /// Remove a recipe and its entries in the producer index.
+    pub fn remove_recipe(&mut self, name: &str) -> Option<Recipe> {
+        let recipe = self.recipes.remove(name)?;
         for output in recipe.outputs.keys() {
-            self.adjacency_list.entry(output.clone()).or_insert_with(Vec::new).push(name.to_string());
+            if let Some(producers) = self.adjacency_list.get_mut(output) {
+                producers.retain(|r| r != name);
+                if producers.is_empty() {
+                    self.adjacency_list.remove(output);
+                }
+            }
         }
+        Some(recipe)
     }
 
+    /// Recipes that produce `target_resource`, fastest first.
     pub fn find_production_path(&self, target_resource: &str) -> Option<Vec<String>> {
-        if !self.adjacency_list.contains_key(target_resource) {
+        self.find_production_path_by(target_resource, PathOrder::Fastest)
+    }
+
+    /// Recipes that produce `target_resource`, in `order`. Ties go to preferred
+    /// recipes, then break by name.
+    pub fn find_production_path_by(&self, target_resource: &str, order: PathOrder) -> Option<Vec<String>> {
+        if !self.produces(target_resource) {
+            return None;
+        }
+        let mut names = self.adjacency_list.get(target_resource)?.clone();
+        let not_preferred = |name: &String| !self.recipes[name].preferred;
+        match order {
+            PathOrder::Fastest => names.sort_by_key(|name| {
+                (self.recipes[name].process_time_seconds, not_preferred(name), name.clone())
+            }),
+            PathOrder::Cheapest => names.sort_by(|a, b| {
+                let cost = |name: &String| self.input_cost(&self.recipes[name]).unwrap_or(f64::INFINITY);
+                cost(a).total_cmp(&cost(b))
+                    .then_with(|| not_preferred(a).cmp(&not_preferred(b)))
+                    .then_with(|| a.cmp(b))
+            }),
+        }
+        Some(names)
+    }
+
+    /// Every recipe producing `resource` with its `metric` score, best first. Ties
+    /// go to preferred recipes, then by name. Empty if nothing produces it.
+    pub fn best_recipes_for(&self, resource: &str, metric: Metric) -> Vec<(String, f64)> {
+        let Some(names) = self.adjacency_list.get(resource) else {
+            return Vec::new();
+        };
+        let mut scored: Vec<(String, f64)> = names.iter().map(|name| {
+            let recipe = &self.recipes[name];
+            let per_run = recipe.outputs.get(resource).copied().unwrap_or(0) as f64;
+            let score = match metric {
+                Metric::Time | Metric::Cost if per_run == 0.0 => f64::INFINITY,
+                Metric::Time => recipe.process_time_seconds as f64 / per_run,
+                Metric::Cost => self.input_cost(recipe).map_or(f64::INFINITY, |cost| cost / per_run),
+                Metric::Yield => per_run,
+            };
+            (name.clone(), score)
+        }).collect();
+
+        let not_preferred = |name: &String| !self.recipes[name].preferred;
+        scored.sort_by(|(a, a_score), (b, b_score)| {
+            let by_score = match metric {
+                Metric::Yield => b_score.total_cmp(a_score),
+                Metric::Time | Metric::Cost => a_score.total_cmp(b_score),
+            };
+            by_score
+                .then_with(|| not_preferred(a).cmp(&not_preferred(b)))
+                .then_with(|| a.cmp(b))
+        });
+        scored
+    }
+
+    /// Value of one run's inputs at stored prices; None if any input is unpriced.
+    fn input_cost(&self, recipe: &Recipe) -> Option<f64> {
+        recipe.inputs.iter()
+            .map(|(resource, qty)| Some(self.resource_price(resource)? * *qty as f64))
+            .sum()
+    }
+
+    pub fn has_recipe(&self, name: &str) -> bool {
+        self.recipes.contains_key(name)
+    }
+
+    /// Whether any recipe outputs `resource`.
+    pub fn produces(&self, resource: &str) -> bool {
+        self.adjacency_list.contains_key(resource)
+    }
+
+    /// All recipes, in no particular order.
+    pub fn recipes(&self) -> impl Iterator<Item = (&String, &Recipe)> {
+        self.recipes.iter()
+    }
+
+    pub fn recipe(&self, name: &str) -> Option<&Recipe> {
+        self.recipes.get(name)
+    }
+
+    /// Number of recipes.
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// Label for the game data release these recipes came from, e.g. a patch number.
+    pub fn set_version(&mut self, version: &str) {
+        self.version = Some(version.to_string());
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Identity of the recipe set: equal for graphs holding the same recipes, in
+    /// any insertion order, and stable across runs and platforms, so it can be
+    /// stored and compared later to detect a stale graph. The first 8 bytes
+    /// (little-endian) of a BLAKE3 hash over the recipes in name order, with
+    /// inputs and outputs sorted. Prices, raw declarations and `version` aren't
+    /// included.
+    pub fn fingerprint(&self) -> u64 {
+        fn put_str(hasher: &mut blake3::Hasher, s: &str) {
+            hasher.update(&(s.len() as u64).to_le_bytes());
+            hasher.update(s.as_bytes());
+        }
+        fn put_side(hasher: &mut blake3::Hasher, side: &HashMap<String, u32>) {
+            hasher.update(&(side.len() as u64).to_le_bytes());
+            for (resource, qty) in side.iter().collect::<BTreeMap<_, _>>() {
+                put_str(hasher, resource);
+                hasher.update(&qty.to_le_bytes());
+            }
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        for (name, recipe) in self.recipes.iter().collect::<BTreeMap<_, _>>() {
+            put_str(&mut hasher, name);
+            put_side(&mut hasher, &recipe.inputs);
+            put_side(&mut hasher, &recipe.outputs);
+            hasher.update(&recipe.process_time_seconds.to_le_bytes());
+            hasher.update(&recipe.energy_cost_kw.to_le_bytes());
+            hasher.update(&[recipe.preferred as u8]);
+            match &recipe.facility {
+                Some(facility) => {
+                    hasher.update(&[1]);
+                    put_str(&mut hasher, facility);
+                }
+                None => {
+                    hasher.update(&[0]);
+                }
+            }
+        }
+        let digest = hasher.finalize();
+        u64::from_le_bytes(digest.as_bytes()[..8].try_into().expect("8 bytes"))
+    }
+
+    /// Every resource named anywhere in the graph (inputs and outputs), sorted.
+    pub fn all_resources(&self) -> BTreeSet<String> {
+        self.recipes.values()
+            .flat_map(|r| r.inputs.keys().chain(r.outputs.keys()))
+            .cloned()
+            .collect()
+    }
+
+    /// The recipe planners use to make `resource`, if any.
+    fn recipe_for(&self, resource: &str) -> Option<&Recipe> {
+        self.recipe_name_for(resource).and_then(|name| self.recipes.get(name))
+    }
+
+    /// The first-added preferred producer of `resource`, else the first-added one.
+    fn recipe_name_for(&self, resource: &str) -> Option<&String> {
+        let producers = self.adjacency_list.get(resource)?;
+        producers.iter().find(|name| self.recipes[*name].preferred).or_else(|| producers.first())
+    }
+
+    /// Full production tree for `quantity` of `target`, using the same recipe choice
+    /// and batch rounding as `raw_material_cost`.
+    pub fn production_plan(&self, target: &str, quantity: u32) -> Result<ProductionPlan> {
+        let root = self.plan_node(target, quantity, &mut Vec::new())?;
+        let mut raw_materials = BTreeMap::new();
+        Self::collect_raw(&root, &mut raw_materials);
+        Ok(ProductionPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
+            target: target.to_string(),
+            quantity,
+            root,
+            raw_materials,
+        })
+    }
+
+    fn plan_node(&self, resource: &str, quantity: u32, stack: &mut Vec<String>) -> Result<ProductionNode> {
+        let raw = ProductionNode {
+            resource: resource.to_string(),
+            quantity,
+            recipe: None,
+            runs: 0,
+            inputs: Vec::new(),
+        };
+        if quantity == 0 {
+            return Ok(raw);
+        }
+        let Some(name) = self.recipe_name_for(resource) else {
+            return Ok(raw);
+        };
+        if stack.iter().any(|r| r == resource) {
+            return Err(anyhow::anyhow!("Production cycle through {}", resource));
+        }
+        if stack.len() >= MAX_PLAN_DEPTH {
+            return Err(anyhow::anyhow!("Production chain for {} exceeds depth {}", resource, MAX_PLAN_DEPTH));
+        }
+
+        let recipe = &self.recipes[name];
+        let runs = Self::runs_needed(recipe, resource, quantity);
+        let mut inputs: Vec<(&String, &u32)> = recipe.inputs.iter().collect();
+        inputs.sort();
+
+        stack.push(resource.to_string());
+        let inputs = inputs.into_iter()
+            .map(|(input, per_run)| self.plan_node(input, per_run.saturating_mul(runs), stack))
+            .collect::<Result<Vec<_>>>()?;
+        stack.pop();
+
+        Ok(ProductionNode { recipe: Some(name.clone()), runs, inputs, ..raw })
+    }
+
+    /// `production_plan`, `bill_of_materials` and timing for `quantity` of
+    /// `target`, bundled for callers that want the whole picture at once.
+    pub fn plan_summary(&self, target: &str, quantity: u32) -> Result<PlanSummary> {
+        let plan = self.production_plan(target, quantity)?;
+        let bill_of_materials = self.bill_of_materials(target, quantity)?.into_iter().collect();
+
+        let mut busy = HashMap::new();
+        let critical_path_seconds = self.critical_path(&plan.root, &mut busy);
+        let bottleneck = busy.iter()
+            .max_by(|(a_name, a), (b_name, b)| a.cmp(b).then_with(|| b_name.cmp(a_name)))
+            .map(|(name, _)| name.clone());
+
+        Ok(PlanSummary {
+            target: plan.target,
+            quantity,
+            raw_materials: plan.raw_materials,
+            bill_of_materials,
+            facilities: busy.len(),
+            critical_path_seconds,
+            bottleneck,
+        })
+    }
+
+    /// Seconds until `node` is done with one facility per step, adding each
+    /// recipe's process time to `busy`.
+    fn critical_path(&self, node: &ProductionNode, busy: &mut HashMap<String, u64>) -> u64 {
+        let Some(name) = &node.recipe else {
+            return 0;
+        };
+        let own = node.runs as u64 * self.recipes[name].process_time_seconds as u64;
+        *busy.entry(name.clone()).or_default() += own;
+        let slowest_input = node.inputs.iter().map(|input| self.critical_path(input, busy)).max().unwrap_or(0);
+        own + slowest_input
+    }
+
+    fn collect_raw(node: &ProductionNode, totals: &mut BTreeMap<String, u32>) {
+        if node.recipe.is_none() {
+            if node.quantity > 0 {
+                let total = totals.entry(node.resource.clone()).or_default();
+                *total = total.saturating_add(node.quantity);
+            }
+            return;
+        }
+        for input in &node.inputs {
+            Self::collect_raw(input, totals);
+        }
+    }
+
+    /// Total raw (unproducible) resources needed to make `quantity` of `target`,
+    /// expanding intermediates recursively. Recipes run in whole batches, so
+    /// quantities round up per step.
+    pub fn raw_material_cost(&self, target: &str, quantity: u32) -> Result<HashMap<String, u32>> {
+        let mut totals = HashMap::new();
+        self.expand_raw(target, quantity, &mut totals, &mut Vec::new())?;
+        Ok(totals)
+    }
+
+    fn expand_raw(&self, resource: &str, quantity: u32, totals: &mut HashMap<String, u32>, stack: &mut Vec<String>) -> Result<()> {
+        if quantity == 0 {
+            return Ok(());
+        }
+        let Some(recipe) = self.recipe_for(resource) else {
+            let total = totals.entry(resource.to_string()).or_default();
+            *total = total.saturating_add(quantity);
+            return Ok(());
+        };
+        if stack.iter().any(|r| r == resource) {
+            return Err(anyhow::anyhow!("Production cycle through {}", resource));
+        }
+        if stack.len() >= MAX_PLAN_DEPTH {
+            return Err(anyhow::anyhow!("Production chain for {} exceeds depth {}", resource, MAX_PLAN_DEPTH));
+        }
+
+        let runs = Self::runs_needed(recipe, resource, quantity);
+        stack.push(resource.to_string());
+        for (input, per_run) in &recipe.inputs {
+            self.expand_raw(input, per_run.saturating_mul(runs), totals, stack)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// Every resource that flows through making `quantity` of `target`: the target
+    /// itself, each intermediate and each raw, with the total units consumed at
+    /// that tier. Unlike `raw_material_cost`, intermediates are listed too, and
+    /// surplus from earlier runs (batch rounding leftovers and byproducts) is
+    /// credited before a recipe runs again, so upstream totals can be lower.
+    /// Inputs are expanded in name order, which decides who gets the credits.
+    pub fn bill_of_materials(&self, target: &str, quantity: u32) -> Result<HashMap<String, u32>> {
+        let mut totals = HashMap::new();
+        self.expand_bom(target, quantity, &mut totals, &mut HashMap::new(), &mut Vec::new())?;
+        Ok(totals)
+    }
+
+    fn expand_bom(
+        &self,
+        resource: &str,
+        quantity: u32,
+        totals: &mut HashMap<String, u32>,
+        surplus: &mut HashMap<String, u32>,
+        stack: &mut Vec<String>,
+    ) -> Result<()> {
+        if quantity == 0 {
+            return Ok(());
+        }
+        let total = totals.entry(resource.to_string()).or_default();
+        *total = total.saturating_add(quantity);
+
+        let on_hand = surplus.entry(resource.to_string()).or_default();
+        let credited = (*on_hand).min(quantity);
+        *on_hand -= credited;
+        let remaining = quantity - credited;
+        let Some(recipe) = self.recipe_for(resource).filter(|_| remaining > 0) else {
+            return Ok(());
+        };
+        if stack.iter().any(|r| r == resource) {
+            return Err(anyhow::anyhow!("Production cycle through {}", resource));
+        }
+        if stack.len() >= MAX_PLAN_DEPTH {
+            return Err(anyhow::anyhow!("Production chain for {} exceeds depth {}", resource, MAX_PLAN_DEPTH));
+        }
+
+        let runs = Self::runs_needed(recipe, resource, remaining);
+        for (output, per_run) in &recipe.outputs {
+            let mut produced = per_run.saturating_mul(runs);
+            if output == resource {
+                produced = produced.saturating_sub(remaining);
+            }
+            let left = surplus.entry(output.clone()).or_default();
+            *left = left.saturating_add(produced);
+        }
+
+        let mut inputs: Vec<(&String, &u32)> = recipe.inputs.iter().collect();
+        inputs.sort();
+        stack.push(resource.to_string());
+        for (input, per_run) in inputs {
+            self.expand_bom(input, per_run.saturating_mul(runs), totals, surplus, stack)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// Length of the longest dependency chain from `target` down to raw materials:
+    /// 0 for a raw (unproducible) resource, 1 for something made only from raws.
+    /// None if the chain is cyclic or deeper than the planner limit.
+    pub fn production_depth(&self, target: &str) -> Option<u32> {
+        self.depth_of(target, &mut Vec::new())
+    }
+
+    fn depth_of(&self, resource: &str, stack: &mut Vec<String>) -> Option<u32> {
+        let Some(recipe) = self.recipe_for(resource) else {
+            return Some(0);
+        };
+        if stack.len() >= MAX_PLAN_DEPTH || stack.iter().any(|r| r == resource) {
             return None;
         }
-        self.adjacency_list.get(target_resource).cloned()
+
+        stack.push(resource.to_string());
+        let mut deepest = 0;
+        for input in recipe.inputs.keys() {
+            deepest = deepest.max(self.depth_of(input, stack)?);
+        }
+        stack.pop();
+        Some(deepest + 1)
+    }
+
+    /// Facilities (parallel copies of each recipe) needed to sustain
+    /// `units_per_hour` of `target`, keyed by recipe name. Rates are continuous,
+    /// so counts are fractional; round up to get buildings.
+    pub fn facilities_required(&self, target: &str, units_per_hour: f64) -> Result<HashMap<String, f64>> {
+        let mut facilities = HashMap::new();
+        self.accumulate_facilities(target, units_per_hour, &mut facilities, &mut Vec::new())?;
+        Ok(facilities)
+    }
+
+    fn accumulate_facilities(
+        &self,
+        resource: &str,
+        units_per_hour: f64,
+        facilities: &mut HashMap<String, f64>,
+        stack: &mut Vec<String>,
+    ) -> Result<()> {
+        let Some(name) = self.recipe_name_for(resource) else {
+            return Ok(());
+        };
+        if stack.iter().any(|r| r == resource) {
+            return Err(anyhow::anyhow!("Production cycle through {}", resource));
+        }
+        if stack.len() >= MAX_PLAN_DEPTH {
+            return Err(anyhow::anyhow!("Production chain for {} exceeds depth {}", resource, MAX_PLAN_DEPTH));
+        }
+
+        let recipe = &self.recipes[name];
+        let per_run = recipe.outputs.get(resource).copied().unwrap_or(0).max(1) as f64;
+        let runs_per_hour = units_per_hour / per_run;
+        // One facility completes 3600 / process_time runs per hour.
+        *facilities.entry(name.clone()).or_default() += runs_per_hour * recipe.process_time_seconds as f64 / 3600.0;
+
+        stack.push(resource.to_string());
+        for (input, qty) in &recipe.inputs {
+            self.accumulate_facilities(input, runs_per_hour * *qty as f64, facilities, stack)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// The recipe needing the most facilities to sustain `units_per_hour` of
+    /// `target`: the step to expand first. None if `target` is raw or the chain is cyclic.
+    pub fn bottleneck(&self, target: &str, units_per_hour: f64) -> Option<String> {
+        self.facilities_required(target, units_per_hour).ok()?
+            .into_iter()
+            .max_by(|(a_name, a), (b_name, b)| a.total_cmp(b).then_with(|| b_name.cmp(a_name)))
+            .map(|(name, _)| name)
+    }
+
+    /// Compare the buildings needed to sustain `units_per_hour` of `target` with
+    /// `available_facilities` (facility type -> count). Recipes sharing a facility
+    /// type share its buildings, so fractional needs are summed per type before
+    /// rounding up. Recipes without a `facility` are bucketed under their own name.
+    pub fn fits_within(
+        &self,
+        target: &str,
+        units_per_hour: f64,
+        available_facilities: &HashMap<String, u32>,
+    ) -> FacilityFeasibility {
+        let required = match self.facilities_required(target, units_per_hour) {
+            Ok(required) => required,
+            Err(e) => return FacilityFeasibility::Unplannable(e.to_string()),
+        };
+
+        let mut by_type: HashMap<String, f64> = HashMap::new();
+        for (name, count) in required {
+            let facility = self.recipes[&name].facility.clone().unwrap_or(name);
+            *by_type.entry(facility).or_default() += count;
+        }
+
+        let shortfall: HashMap<String, u32> = by_type.into_iter()
+            .filter_map(|(facility, count)| {
+                // Absorb float noise so exactly-full buildings don't round up to one more.
+                let needed = (count - 1e-9).ceil().max(0.0) as u32;
+                let have = available_facilities.get(&facility).copied().unwrap_or(0);
+                (needed > have).then(|| (facility, needed - have))
+            })
+            .collect();
+
+        if shortfall.is_empty() {
+            FacilityFeasibility::Feasible
+        } else {
+            FacilityFeasibility::Shortfall(shortfall)
+        }
+    }
+
+    /// Whole runs of `recipe` needed for `quantity` units of `output`.
+    fn runs_needed(recipe: &Recipe, output: &str, quantity: u32) -> u32 {
+        let per_run = recipe.outputs.get(output).copied().unwrap_or(0).max(1);
+        quantity.div_ceil(per_run)
+    }
+
+    /// Can `quantity` of `target` be built from `inventory`? Stock is drawn down at
+    /// every tier (intermediates on hand are used before producing more), and any
+    /// gap is reported at the raw level. Cyclic or unproducible resources count as raw.
+    pub fn can_produce(&self, target: &str, quantity: u32, inventory: &HashMap<String, u32>) -> ProductionFeasibility {
+        let mut stock = inventory.clone();
+        let mut missing = HashMap::new();
+        self.draw_down(target, quantity, &mut stock, &mut missing, &mut Vec::new());
+
+        if missing.is_empty() {
+            ProductionFeasibility::Feasible
+        } else {
+            ProductionFeasibility::Shortfall(missing)
+        }
+    }
+
+    fn draw_down(
+        &self,
+        resource: &str,
+        quantity: u32,
+        stock: &mut HashMap<String, u32>,
+        missing: &mut HashMap<String, u32>,
+        stack: &mut Vec<String>,
+    ) {
+        let on_hand = stock.entry(resource.to_string()).or_default();
+        let used = (*on_hand).min(quantity);
+        *on_hand -= used;
+        let remaining = quantity - used;
+        if remaining == 0 {
+            return;
+        }
+
+        let recipe = self.recipe_for(resource)
+            .filter(|_| stack.len() < MAX_PLAN_DEPTH && !stack.iter().any(|r| r == resource));
+        let Some(recipe) = recipe else {
+            let gap = missing.entry(resource.to_string()).or_default();
+            *gap = gap.saturating_add(remaining);
+            return;
+        };
+
+        let runs = Self::runs_needed(recipe, resource, remaining);
+        stack.push(resource.to_string());
+        for (input, per_run) in &recipe.inputs {
+            self.draw_down(input, per_run.saturating_mul(runs), stock, missing, stack);
+        }
+        stack.pop();
+    }
+
+    /// Net resource flow of one run of a recipe: outputs minus inputs.
+    /// Negative = consumed, positive = produced, zero = catalyst (appears on both
+    /// sides in equal amounts). Returns None if the recipe doesn't exist.
+    pub fn net_flow(&self, recipe_name: &str) -> Option<HashMap<String, i64>> {
+        let recipe = self.recipes.get(recipe_name)?;
+
+        let mut flow: HashMap<String, i64> = HashMap::new();
+        for (input, qty) in &recipe.inputs {
+            *flow.entry(input.clone()).or_default() -= *qty as i64;
+        }
+        for (output, qty) in &recipe.outputs {
+            *flow.entry(output.clone()).or_default() += *qty as i64;
+        }
+        Some(flow)
+    }
+
+    /// Units of `output_resource` per second from one facility running the recipe
+    /// back to back. Instant recipes (`process_time_seconds == 0`) return
+    /// `f64::INFINITY` rather than an error, so they sort as the fastest option
+    /// and need no facilities. None if the recipe is unknown or doesn't produce
+    /// a positive quantity of that output.
+    pub fn output_rate(&self, recipe_name: &str, output_resource: &str) -> Option<f64> {
+        let recipe = self.recipes.get(recipe_name)?;
+        let per_run = *recipe.outputs.get(output_resource).filter(|&&qty| qty > 0)? as f64;
+        if recipe.process_time_seconds == 0 {
+            return Some(f64::INFINITY);
+        }
+        Some(per_run / recipe.process_time_seconds as f64)
+    }
+
+    /// What `facilities` copies of a recipe turn over in `duration`, counting only
+    /// completed runs (a batch still in progress when the window closes is
+    /// dropped). Outputs map to the quantity produced and inputs to the quantity
+    /// consumed; a resource on both sides reports its output (see `net_flow`).
+    /// Empty if the recipe doesn't exist or has no process time.
+    pub fn yield_over(&self, recipe_name: &str, facilities: u32, duration: std::time::Duration) -> HashMap<String, u64> {
+        let Some(recipe) = self.recipes.get(recipe_name).filter(|r| r.process_time_seconds > 0) else {
+            return HashMap::new();
+        };
+        let runs = (duration.as_secs() / recipe.process_time_seconds as u64).saturating_mul(facilities as u64);
+
+        let mut totals = HashMap::new();
+        for (resource, qty) in recipe.inputs.iter().chain(&recipe.outputs) {
+            totals.insert(resource.clone(), runs.saturating_mul(*qty as u64));
+        }
+        totals
+    }
+
+    /// Inputs needed per single unit of `output_resource` from one recipe run,
+    /// e.g. 2.5 Iron Ore per Steel for 250 Ore -> 100 Steel. None if the recipe
+    /// is unknown or doesn't produce a positive quantity of that output.
+    pub fn per_unit_inputs(&self, recipe_name: &str, output_resource: &str) -> Option<HashMap<String, f64>> {
+        let recipe = self.recipes.get(recipe_name)?;
+        let per_run = *recipe.outputs.get(output_resource).filter(|&&qty| qty > 0)? as f64;
+        Some(recipe.inputs.iter().map(|(input, qty)| (input.clone(), *qty as f64 / per_run)).collect())
+    }
+
+    /// The recipe book as CSV with header `recipe,role,resource,quantity,process_time`,
+    /// one row per input (`role` = `input`) or output (`output`). Rows are sorted by
+    /// recipe, then inputs before outputs, then resource, so output is stable.
+    /// Energy costs, preferences, prices and raw declarations aren't included.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        let mut names: Vec<&String> = self.recipes.keys().collect();
+        names.sort();
+        for name in names {
+            let recipe = &self.recipes[name];
+            for (role, side) in [("input", &recipe.inputs), ("output", &recipe.outputs)] {
+                let mut rows: Vec<(&String, &u32)> = side.iter().collect();
+                rows.sort();
+                for (resource, qty) in rows {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{}\n",
+                        csv_field(name), role, csv_field(resource), qty, recipe.process_time_seconds
+                    ));
+                }
+            }
+        }
+        csv
+    }
+
+    /// Rebuild a graph from `to_csv` output. The header line is optional. Only the
+    /// listed recipes are added (not the built-in one); energy costs are 0 and
+    /// facilities unset.
+    pub fn from_csv(csv: &str) -> Result<Self> {
+        let mut recipes: BTreeMap<String, Recipe> = BTreeMap::new();
+        for (line_no, line) in csv_records(csv) {
+            if line.trim().is_empty() || (line_no == 1 && line == CSV_HEADER) {
+                continue;
+            }
+            let fields = parse_csv_line(line).ok_or_else(|| anyhow::anyhow!("Line {}: unterminated quote", line_no))?;
+            let [name, role, resource, qty, time] = fields.as_slice() else {
+                return Err(anyhow::anyhow!("Line {}: expected 5 fields, got {}", line_no, fields.len()));
+            };
+            let qty: u32 = qty.trim().parse().map_err(|_| anyhow::anyhow!("Line {}: invalid quantity {:?}", line_no, qty))?;
+            let time: u32 = time.trim().parse().map_err(|_| anyhow::anyhow!("Line {}: invalid process_time {:?}", line_no, time))?;
+
+            let recipe = recipes.entry(name.clone()).or_insert_with(|| Recipe {
+                inputs: HashMap::new(),
+                outputs: HashMap::new(),
+                process_time_seconds: time,
+                energy_cost_kw: 0,
+                preferred: false,
+                facility: None,
+            });
+            if recipe.process_time_seconds != time {
+                return Err(anyhow::anyhow!("Line {}: process_time for {} differs from earlier rows", line_no, name));
+            }
+            let side = match role.as_str() {
+                "input" => &mut recipe.inputs,
+                "output" => &mut recipe.outputs,
+                other => return Err(anyhow::anyhow!("Line {}: role must be input or output, got {:?}", line_no, other)),
+            };
+            side.insert(resource.clone(), qty);
+        }
+        Ok(recipes.into_iter().collect())
+    }
+
+    /// Every recipe consuming `resource`, directly or through intermediates made
+    /// from it: the recipes whose margins move when its price does.
+    pub fn recipes_affected_by(&self, resource: &str) -> HashSet<String> {
+        // Input -> recipes consuming it, built once so the walk is linear in the graph.
+        let mut consumers: HashMap<&str, Vec<(&str, &Recipe)>> = HashMap::new();
+        for (name, recipe) in &self.recipes {
+            for input in recipe.inputs.keys() {
+                consumers.entry(input.as_str()).or_default().push((name.as_str(), recipe));
+            }
+        }
+
+        let mut affected = HashSet::new();
+        let mut seen = HashSet::from([resource]);
+        let mut pending = vec![resource];
+
+        while let Some(current) = pending.pop() {
+            for &(name, recipe) in consumers.get(current).into_iter().flatten() {
+                if !affected.insert(name.to_string()) {
+                    continue;
+                }
+                for output in recipe.outputs.keys() {
+                    if seen.insert(output.as_str()) {
+                        pending.push(output.as_str());
+                    }
+                }
+            }
+        }
+        affected
+    }
+
+    /// Set the market value of one unit of `resource`. Prices are optional; planners
+    /// that only use time ignore them.
+    pub fn set_resource_price(&mut self, resource: &str, price: f64) {
+        self.resource_prices.insert(resource.to_string(), price);
+    }
+
+    pub fn set_resource_prices(&mut self, prices: HashMap<String, f64>) {
+        self.resource_prices.extend(prices);
+    }
+
+    pub fn resource_price(&self, resource: &str) -> Option<f64> {
+        self.resource_prices.get(resource).copied()
+    }
+
+    /// Value of one run's outputs minus its inputs at the graph's stored prices.
+    /// None if the recipe is unknown or any of its resources is unpriced.
+    pub fn recipe_margin(&self, name: &str) -> Option<f64> {
+        let recipe = self.recipes.get(name)?;
+        let output_value: Option<f64> = recipe.outputs.iter()
+            .map(|(resource, qty)| Some(self.resource_price(resource)? * *qty as f64))
+            .sum();
+        Some(output_value? - self.input_cost(recipe)?)
+    }
+
+    /// Raw-material cost embedded in one unit of `target` at `raw_prices`: the
+    /// total, and each raw resource's share of it. Quantities are continuous (no
+    /// batch rounding) and byproducts aren't credited. Fails naming the first
+    /// unpriced raw resource (alphabetically), or if the chain is cyclic.
+    pub fn unit_cost_breakdown(&self, target: &str, raw_prices: &HashMap<String, f64>) -> Result<(f64, HashMap<String, f64>)> {
+        let mut raw = HashMap::new();
+        self.accumulate_raw_per_unit(target, 1.0, &mut raw, &mut Vec::new())?;
+
+        let mut unpriced: Vec<&String> = raw.keys().filter(|r| !raw_prices.contains_key(*r)).collect();
+        unpriced.sort();
+        if let Some(resource) = unpriced.first() {
+            return Err(anyhow::anyhow!("No price for raw resource {} (needed for {})", resource, target));
+        }
+
+        let breakdown: HashMap<String, f64> = raw.into_iter()
+            .map(|(resource, qty)| {
+                let cost = raw_prices[&resource] * qty;
+                (resource, cost)
+            })
+            .collect();
+        Ok((breakdown.values().sum(), breakdown))
+    }
+
+    fn accumulate_raw_per_unit(
+        &self,
+        resource: &str,
+        quantity: f64,
+        raw: &mut HashMap<String, f64>,
+        stack: &mut Vec<String>,
+    ) -> Result<()> {
+        let Some(name) = self.recipe_name_for(resource) else {
+            *raw.entry(resource.to_string()).or_default() += quantity;
+            return Ok(());
+        };
+        if stack.iter().any(|r| r == resource) {
+            return Err(anyhow::anyhow!("Production cycle through {}", resource));
+        }
+        if stack.len() >= MAX_PLAN_DEPTH {
+            return Err(anyhow::anyhow!("Production chain for {} exceeds depth {}", resource, MAX_PLAN_DEPTH));
+        }
+        let inputs = self.per_unit_inputs(name, resource)
+            .ok_or_else(|| anyhow::anyhow!("Recipe {} produces no {}", name, resource))?;
+
+        stack.push(resource.to_string());
+        for (input, per_unit) in inputs {
+            self.accumulate_raw_per_unit(&input, quantity * per_unit, raw, stack)?;
+        }
+        stack.pop();
+        Ok(())
     }
 
     /// Calculate profitability of a recipe given current market prices.
@@ -0,0 +1,336 @@
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use starknet::providers::jsonrpc::{JsonRpcClientError, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport};
+use starknet::providers::ProviderError;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Sent as `User-Agent` unless the builder overrides it.
+pub const DEFAULT_USER_AGENT: &str = concat!("stark_pyrust/", env!("CARGO_PKG_VERSION"));
+
+/// Default limit on establishing a connection (TCP plus TLS handshake).
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default limit on a whole request, from connecting to the last body byte.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP timeouts of an [`RpcTransport`]. A short `connect` fails fast on
+/// unreachable hosts; a long `request` tolerates slow, large queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub connect: Duration,
+    pub request: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts { connect: DEFAULT_CONNECT_TIMEOUT, request: DEFAULT_REQUEST_TIMEOUT }
+    }
+}
+
+/// Observer of raw JSON-RPC traffic: called with the method name, the request
+/// object and the response object (or the raw body as a string if it isn't JSON).
+/// Batches are reported entry by entry. Throttled and failed HTTP requests have
+/// no response body and aren't reported.
+#[derive(Clone)]
+pub struct Inspector(Arc<InspectFn>);
+
+type InspectFn = dyn Fn(&str, &Value, &Value) + Send + Sync;
+
+impl Inspector {
+    pub fn new(f: impl Fn(&str, &Value, &Value) + Send + Sync + 'static) -> Self {
+        Inspector(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for Inspector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Inspector")
+    }
+}
+
+/// JSON-RPC over HTTP, like starknet's `HttpTransport`, but keeps the HTTP status
+/// and `Retry-After` header of throttled/unavailable responses instead of
+/// failing on the (usually non-JSON) error body.
+///
+/// Cloning shares the connection pool.
+#[derive(Debug, Clone)]
+pub struct RpcTransport {
+    client: Client,
+    url: Url,
+    user_agent: String,
+    timeouts: Timeouts,
+    inspector: Option<Inspector>,
+}
+
+#[derive(Debug)]
+pub enum RpcTransportError {
+    Reqwest(reqwest::Error),
+    Json(serde_json::Error),
+    /// 429 or 5xx from the endpoint. `retry_after` is the server's requested delay, if any.
+    Status { status: StatusCode, retry_after: Option<Duration> },
+    /// The owning client was closed; no request was sent.
+    Closed,
+    /// The endpoint answered a batch with something other than an array of
+    /// responses, i.e. it doesn't support JSON-RPC batching.
+    BatchUnsupported,
+    /// The node answered with a JSON-RPC error object.
+    Rpc(RpcError),
+}
+
+impl fmt::Display for RpcTransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcTransportError::Reqwest(e) => write!(f, "{}", e),
+            RpcTransportError::Json(e) => write!(f, "{}", e),
+            RpcTransportError::Status { status, retry_after: Some(d) } => {
+                write!(f, "HTTP {} (retry after {}s)", status, d.as_secs())
+            }
+            RpcTransportError::Status { status, retry_after: None } => write!(f, "HTTP {}", status),
+            RpcTransportError::Closed => write!(f, "client closed"),
+            RpcTransportError::BatchUnsupported => write!(f, "endpoint does not support JSON-RPC batches"),
+            RpcTransportError::Rpc(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RpcTransportError {}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<T> {
+    id: u64,
+    jsonrpc: &'static str,
+    method: JsonRpcMethod,
+    params: T,
+}
+
+impl RpcTransport {
+    /// Fails only if `user_agent` isn't a valid header value.
+    pub fn new(url: Url, user_agent: &str, timeouts: Timeouts) -> Result<Self, reqwest::Error> {
+        let builder = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request);
+        // Bulk reads (blocks with txs, events) compress well; with the `gzip`
+        // feature we advertise it and reqwest inflates bodies transparently.
+        #[cfg(feature = "gzip")]
+        let builder = builder.gzip(true);
+        let client = builder.build()?;
+        Ok(RpcTransport { client, url, user_agent: user_agent.to_string(), timeouts, inspector: None })
+    }
+
+    /// Report every request/response pair to `inspector`.
+    pub fn inspect(mut self, inspector: Option<Inspector>) -> Self {
+        self.inspector = inspector;
+        self
+    }
+
+    /// A transport for the same endpoint with a new connection pool, dropping
+    /// whatever (possibly stale) keep-alive connections this one holds.
+    pub fn reconnect(&self) -> Result<Self, reqwest::Error> {
+        Ok(Self::new(self.url.clone(), &self.user_agent, self.timeouts)?.inspect(self.inspector.clone()))
+    }
+}
+
+/// One request of a JSON-RPC batch.
+#[derive(Debug, Clone)]
+pub struct BatchCall {
+    pub method: JsonRpcMethod,
+    pub params: serde_json::Value,
+}
+
+/// A JSON-RPC error object, as the node sent it. See
+/// `crate::error::ClientError::from_rpc` for the Starknet codes callers usually match on.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)
+    }
+}
+
+/// Just the `error` member of a response, to catch error objects before they're
+/// decoded into the provider's own (lossy) error types.
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    id: Option<u64>,
+    result: Option<serde_json::Value>,
+    error: Option<RpcError>,
+}
+
+impl RpcTransport {
+    /// Send `calls` as one JSON-RPC batch (a single HTTP request) and return each
+    /// entry's `result` or error, in `calls` order. Servers may answer batch
+    /// entries in any order; they are matched back by id.
+    pub async fn send_batch(&self, calls: &[BatchCall]) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcTransportError> {
+        let requests: Vec<JsonRpcRequest<&serde_json::Value>> = calls.iter().enumerate()
+            .map(|(id, call)| JsonRpcRequest { id: id as u64, jsonrpc: "2.0", method: call.method, params: &call.params })
+            .collect();
+        let body = serde_json::to_string(&requests).map_err(RpcTransportError::Json)?;
+
+        let text = self.post(body).await?;
+        if let Some(inspector) = &self.inspector {
+            inspect_batch(inspector, &requests, &text);
+        }
+        let value: serde_json::Value = serde_json::from_str(&text).map_err(RpcTransportError::Json)?;
+        if !value.is_array() {
+            return Err(RpcTransportError::BatchUnsupported);
+        }
+        let responses: Vec<BatchResponse> = serde_json::from_value(value).map_err(RpcTransportError::Json)?;
+
+        let mut results: Vec<Option<Result<serde_json::Value, RpcError>>> = vec![None; calls.len()];
+        for response in responses {
+            let Some(slot) = response.id.and_then(|id| results.get_mut(id as usize)) else {
+                continue;
+            };
+            *slot = Some(match (response.result, response.error) {
+                (_, Some(error)) => Err(error),
+                (Some(result), None) => Ok(result),
+                (None, None) => Ok(serde_json::Value::Null),
+            });
+        }
+        Ok(results.into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(RpcError { code: -32603, message: "missing from batch response".to_string(), data: None })))
+            .collect())
+    }
+
+    /// POST a JSON body and return the response text, mapping throttling and
+    /// server errors to `RpcTransportError::Status`.
+    async fn post(&self, body: String) -> Result<String, RpcTransportError> {
+        let response = self.client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(RpcTransportError::Reqwest)?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(RpcTransportError::Status {
+                status,
+                retry_after: parse_retry_after(response.headers()),
+            });
+        }
+
+        response.text().await.map_err(RpcTransportError::Reqwest)
+    }
+}
+
+#[async_trait]
+impl JsonRpcTransport for RpcTransport {
+    type Error = RpcTransportError;
+
+    async fn send_request<P, R>(&self, method: JsonRpcMethod, params: P) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let request = JsonRpcRequest { id: 1, jsonrpc: "2.0", method, params };
+        let body = serde_json::to_string(&request).map_err(RpcTransportError::Json)?;
+
+        let text = self.post(body).await?;
+        if let Some(inspector) = &self.inspector {
+            let request = serde_json::to_value(&request).unwrap_or(Value::Null);
+            (inspector.0)(&method_name(method), &request, &response_value(&text));
+        }
+        if let Ok(ErrorEnvelope { error: Some(error) }) = serde_json::from_str(&text) {
+            return Err(RpcTransportError::Rpc(error));
+        }
+        serde_json::from_str(&text).map_err(RpcTransportError::Json)
+    }
+}
+
+/// Wire name of `method`, e.g. `starknet_call`.
+fn method_name(method: JsonRpcMethod) -> String {
+    match serde_json::to_value(method) {
+        Ok(Value::String(name)) => name,
+        _ => format!("{:?}", method),
+    }
+}
+
+/// The response body as JSON, or as a string if it doesn't parse.
+fn response_value(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// Report each entry of a batch with its response, matched by id. Entries the
+/// server left out are reported with a null response.
+fn inspect_batch(inspector: &Inspector, requests: &[JsonRpcRequest<&Value>], text: &str) {
+    let body = response_value(text);
+    let responses = body.as_array();
+    for request in requests {
+        let response = responses
+            .and_then(|all| all.iter().find(|r| r["id"] == request.id))
+            .cloned()
+            .unwrap_or(if responses.is_some() { Value::Null } else { body.clone() });
+        let request_value = serde_json::to_value(request).unwrap_or(Value::Null);
+        (inspector.0)(&method_name(request.method), &request_value, &response);
+    }
+}
+
+/// `Retry-After` in its delta-seconds form. HTTP-date values are ignored and the
+/// caller falls back to its own backoff.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+fn transport_error(err: &ProviderError) -> Option<&RpcTransportError> {
+    match err {
+        ProviderError::Other(inner) => match inner.as_any().downcast_ref::<JsonRpcClientError<RpcTransportError>>()? {
+            JsonRpcClientError::TransportError(e) => Some(e),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `err` failed at the HTTP/connection level (refused, reset, timed out)
+/// rather than with a response from the endpoint.
+pub fn is_connection_error(err: &ProviderError) -> bool {
+    matches!(transport_error(err), Some(RpcTransportError::Reqwest(_)))
+}
+
+/// The JSON-RPC error object behind `err`, if the node returned one.
+pub fn rpc_error(err: &ProviderError) -> Option<&RpcError> {
+    match transport_error(err)? {
+        RpcTransportError::Rpc(e) => Some(e),
+        _ => None,
+    }
+}
+
+/// The delay the endpoint asked for via `Retry-After`, if the error carries one.
+pub fn retry_after(err: &ProviderError) -> Option<Duration> {
+    match transport_error(err)? {
+        RpcTransportError::Status { retry_after, .. } => *retry_after,
+        _ => None,
+    }
+}
+
+/// Whether retrying (possibly on another provider) might succeed. Node-level
+/// errors such as "contract not found" are deterministic and are not retried.
+pub fn is_retryable(err: &ProviderError) -> bool {
+    match err {
+        ProviderError::RateLimited => true,
+        _ => matches!(
+            transport_error(err),
+            Some(RpcTransportError::Reqwest(_)) | Some(RpcTransportError::Status { .. })
+        ),
+    }
+}
@@ -0,0 +1,634 @@
+//! Structured return types shared by the client and its Python bindings.
+
+use anyhow::Result;
+use crate::units::format_balance;
+use starknet::core::types::{FieldElement, ResourceBounds, ResourceBoundsMapping};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Canonical address form: `0x` plus 64 lowercase hex digits, leading zeros kept,
+/// so formatted addresses compare equal exactly when the felts do.
+pub fn felt_to_address_string(felt: &FieldElement) -> String {
+    format!("{:#064x}", felt)
+}
+
+/// Parse a hex address (any case, with or without leading zeros) and return it in
+/// canonical form. Fails if it isn't valid hex or is out of the field's range.
+pub fn normalize_address(address: &str) -> Result<String> {
+    Ok(felt_to_address_string(&parse_address(address)?))
+}
+
+/// The Stark field prime, `2^251 + 17 * 2^192 + 1`. Every felt, and so every
+/// address, is an integer in `[0, STARK_PRIME)`.
+pub const STARK_PRIME: U256 = U256 { high: 0x0800_0000_0000_0011_0000_0000_0000_0000, low: 1 };
+
+/// Whether `value` (`0x`-prefixed hex or plain decimal) is a felt, i.e. below
+/// [`STARK_PRIME`]. Larger values would otherwise be reduced or rejected
+/// inconsistently depending on the parsing path.
+pub fn is_valid_felt(value: &str) -> bool {
+    let value = value.trim();
+    let parsed = match strip_hex_prefix(value) {
+        Some(hex) => U256::from_hex(hex),
+        None => value.parse().ok(),
+    };
+    parsed.is_some_and(|v| v < STARK_PRIME)
+}
+
+/// Parse a hex address (`0x` prefix optional), refusing values outside the field.
+pub fn parse_address(address: &str) -> Result<FieldElement> {
+    let trimmed = address.trim();
+    let hex = strip_hex_prefix(trimmed).unwrap_or(trimmed);
+    if !is_valid_felt(&format!("0x{}", hex)) {
+        return Err(anyhow::anyhow!("Invalid address {:?}: not a hex value below the Stark prime", address));
+    }
+    FieldElement::from_hex_be(&hex.to_ascii_lowercase()).map_err(|e| anyhow::anyhow!("Invalid address {:?}: {}", address, e))
+}
+
+/// A contract or account address: a felt that was validated on the way in.
+/// Parses from hex strings (`FromStr`), displays in the canonical
+/// [`felt_to_address_string`] form, and serializes as that string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address(pub FieldElement);
+
+impl Address {
+    pub fn felt(&self) -> FieldElement {
+        self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_address(s).map(Address)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&felt_to_address_string(&self.0))
+    }
+}
+
+impl From<FieldElement> for Address {
+    fn from(felt: FieldElement) -> Self {
+        Address(felt)
+    }
+}
+
+impl From<Address> for FieldElement {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything the client accepts where an address goes: an [`Address`], a
+/// `FieldElement`, or a hex string validated with [`parse_address`]. Strings
+/// are fallible, so this is a conversion trait rather than `Into<Address>`.
+pub trait IntoAddress {
+    fn into_address(self) -> Result<Address>;
+}
+
+impl IntoAddress for Address {
+    fn into_address(self) -> Result<Address> {
+        Ok(self)
+    }
+}
+
+impl IntoAddress for FieldElement {
+    fn into_address(self) -> Result<Address> {
+        Ok(Address(self))
+    }
+}
+
+impl IntoAddress for &str {
+    fn into_address(self) -> Result<Address> {
+        self.parse()
+    }
+}
+
+impl IntoAddress for &String {
+    fn into_address(self) -> Result<Address> {
+        self.parse()
+    }
+}
+
+impl IntoAddress for String {
+    fn into_address(self) -> Result<Address> {
+        self.parse()
+    }
+}
+
+fn strip_hex_prefix(value: &str) -> Option<&str> {
+    value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))
+}
+
+/// Progress of a submitted transaction, in the order it moves through them.
+/// Rejected and reverted transactions end `wait_for_acceptance` with an error
+/// instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TxStatus {
+    /// Known to the node, not yet in a block.
+    Received,
+    AcceptedOnL2,
+    AcceptedOnL1,
+}
+
+/// Chain head snapshot from `get_network_status`.
+///
+/// Gas prices are the block's L1 gas price in base units: wei for ETH-denominated
+/// fees, fri (10^-18 STRK) for STRK-denominated (v3) fees. The L1 data-gas price is
+/// not part of the JSON-RPC spec version this crate targets (0.6), so it isn't
+/// reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    /// Block height; 0 when `is_pending` (pending blocks have no number yet).
+    pub block_number: u64,
+    pub l1_gas_price_wei: u128,
+    pub l1_gas_price_fri: u128,
+    pub is_pending: bool,
+}
+
+impl NetworkStatus {
+    /// L1 gas price in gwei. `l1_gas_price_wei` remains the canonical value.
+    pub fn gas_price_gwei(&self) -> f64 {
+        wei_to_unit(self.l1_gas_price_wei, 1_000_000_000)
+    }
+
+    /// L1 gas price in ETH.
+    pub fn gas_price_eth(&self) -> f64 {
+        wei_to_unit(self.l1_gas_price_wei, 1_000_000_000_000_000_000)
+    }
+}
+
+/// Gas price at `percentile` (0-100, clamped) of a `gas_price_history` window,
+/// by nearest rank: 50 is the median, 90 a price most recent blocks stayed under.
+/// None for an empty window.
+pub fn suggested_gas_price(history: &[(u64, u128)], percentile: f64) -> Option<u128> {
+    let mut prices: Vec<u128> = history.iter().map(|&(_, price)| price).collect();
+    prices.sort_unstable();
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * prices.len() as f64).ceil() as usize;
+    prices.get(rank.saturating_sub(1)).copied()
+}
+
+/// `wei / per_unit`, dividing in integers first so large values only lose the
+/// precision of the final f64, not of an f64 wei intermediate.
+fn wei_to_unit(wei: u128, per_unit: u128) -> f64 {
+    (wei / per_unit) as f64 + (wei % per_unit) as f64 / per_unit as f64
+}
+
+impl fmt::Display for NetworkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {} @ {} gwei, pending={}",
+            self.block_number,
+            format_balance(self.l1_gas_price_wei, 9),
+            self.is_pending
+        )
+    }
+}
+
+/// Kind of a transaction from [`BlockTransaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionType {
+    Invoke,
+    L1Handler,
+    Declare,
+    Deploy,
+    DeployAccount,
+}
+
+/// A transaction from `get_block_transactions`, reduced to what activity
+/// analysis needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockTransaction {
+    /// Canonical form (see `felt_to_address_string`).
+    pub hash: String,
+    pub tx_type: TransactionType,
+    /// Sending account, or the target contract for v0 invokes and L1 handlers.
+    /// None for deploys, which have no sender.
+    pub sender: Option<String>,
+    /// Execute calldata, or constructor calldata for deploys. Empty for declares.
+    pub calldata: Vec<FieldElement>,
+}
+
+impl From<starknet::core::types::Transaction> for BlockTransaction {
+    fn from(tx: starknet::core::types::Transaction) -> Self {
+        use starknet::core::types::{
+            DeclareTransaction as Declare, DeployAccountTransaction as DeployAccount,
+            InvokeTransaction as Invoke, Transaction,
+        };
+
+        let hash = felt_to_address_string(tx.transaction_hash());
+        let (tx_type, sender, calldata) = match tx {
+            Transaction::Invoke(Invoke::V0(t)) => (TransactionType::Invoke, Some(t.contract_address), t.calldata),
+            Transaction::Invoke(Invoke::V1(t)) => (TransactionType::Invoke, Some(t.sender_address), t.calldata),
+            Transaction::Invoke(Invoke::V3(t)) => (TransactionType::Invoke, Some(t.sender_address), t.calldata),
+            Transaction::L1Handler(t) => (TransactionType::L1Handler, Some(t.contract_address), t.calldata),
+            Transaction::Declare(d) => {
+                let sender = match d {
+                    Declare::V0(t) => t.sender_address,
+                    Declare::V1(t) => t.sender_address,
+                    Declare::V2(t) => t.sender_address,
+                    Declare::V3(t) => t.sender_address,
+                };
+                (TransactionType::Declare, Some(sender), Vec::new())
+            }
+            Transaction::Deploy(t) => (TransactionType::Deploy, None, t.constructor_calldata),
+            Transaction::DeployAccount(DeployAccount::V1(t)) => (TransactionType::DeployAccount, None, t.constructor_calldata),
+            Transaction::DeployAccount(DeployAccount::V3(t)) => (TransactionType::DeployAccount, None, t.constructor_calldata),
+        };
+        BlockTransaction { hash, tx_type, sender: sender.as_ref().map(felt_to_address_string), calldata }
+    }
+}
+
+/// Unit of a fee amount: wei for ETH-paid (pre-v3) transactions, fri for STRK-paid (v3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeUnit {
+    Wei,
+    Fri,
+}
+
+impl fmt::Display for FeeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FeeUnit::Wei => "wei",
+            FeeUnit::Fri => "fri",
+        })
+    }
+}
+
+/// Fee estimate for one transaction, in base units of `unit`, broken out per
+/// resource as in the v3 (resource-bounds) fee model.
+///
+/// Nodes on JSON-RPC 0.6 (the spec this crate targets) only report L1 gas, so the
+/// L2 gas and L1 data-gas fields are zero for now.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub l1_gas_consumed: u128,
+    pub l1_gas_price: u128,
+    pub l2_gas_consumed: u128,
+    pub l2_gas_price: u128,
+    pub l1_data_gas_consumed: u128,
+    pub l1_data_gas_price: u128,
+    /// Total fee as reported by the node.
+    pub overall_fee: u128,
+    pub unit: FeeUnit,
+}
+
+/// Suggested per-resource limits for a v3 transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeResourceBounds {
+    pub l1_gas: ResourceBounds,
+    pub l2_gas: ResourceBounds,
+    pub l1_data_gas: ResourceBounds,
+}
+
+impl From<FeeResourceBounds> for ResourceBoundsMapping {
+    /// v3 transactions in JSON-RPC 0.6 carry no L1 data-gas bound, so it is dropped.
+    fn from(bounds: FeeResourceBounds) -> Self {
+        ResourceBoundsMapping { l1_gas: bounds.l1_gas, l2_gas: bounds.l2_gas }
+    }
+}
+
+impl FeeEstimate {
+    /// `max_fee` for a legacy (v1) transaction: `overall_fee` scaled by `multiplier`.
+    pub fn max_fee(&self, multiplier: f64) -> u128 {
+        scale_up(self.overall_fee, multiplier)
+    }
+
+    /// Resource bounds for a v3 transaction: each amount and price scaled by
+    /// `multiplier` (e.g. 1.5 to tolerate a 50% move before inclusion).
+    pub fn resource_bounds(&self, multiplier: f64) -> FeeResourceBounds {
+        let bound = |consumed: u128, price: u128| ResourceBounds {
+            max_amount: u64::try_from(scale_up(consumed, multiplier)).unwrap_or(u64::MAX),
+            max_price_per_unit: scale_up(price, multiplier),
+        };
+        FeeResourceBounds {
+            l1_gas: bound(self.l1_gas_consumed, self.l1_gas_price),
+            l2_gas: bound(self.l2_gas_consumed, self.l2_gas_price),
+            l1_data_gas: bound(self.l1_data_gas_consumed, self.l1_data_gas_price),
+        }
+    }
+}
+
+/// `value * multiplier`, rounded up, with the multiplier taken to 4 decimal places
+/// so round numbers stay exact.
+fn scale_up(value: u128, multiplier: f64) -> u128 {
+    let bps = (multiplier.max(0.0) * 10_000.0).round() as u128;
+    value.saturating_mul(bps).div_ceil(10_000)
+}
+
+impl TryFrom<starknet::core::types::FeeEstimate> for FeeEstimate {
+    type Error = anyhow::Error;
+
+    fn try_from(estimate: starknet::core::types::FeeEstimate) -> Result<Self> {
+        use starknet::core::types::PriceUnit;
+
+        let to_u128 = |felt: FieldElement| -> Result<u128> {
+            format!("{}", felt).parse().map_err(|_| anyhow::anyhow!("Fee value {:#x} exceeds u128", felt))
+        };
+        Ok(FeeEstimate {
+            l1_gas_consumed: to_u128(estimate.gas_consumed)?,
+            l1_gas_price: to_u128(estimate.gas_price)?,
+            l2_gas_consumed: 0,
+            l2_gas_price: 0,
+            l1_data_gas_consumed: 0,
+            l1_data_gas_price: 0,
+            overall_fee: to_u128(estimate.overall_fee)?,
+            unit: match estimate.unit {
+                PriceUnit::Wei => FeeUnit::Wei,
+                PriceUnit::Fri => FeeUnit::Fri,
+            },
+        })
+    }
+}
+
+impl fmt::Display for FeeEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fee {} {} (l1 gas {} @ {}", self.overall_fee, self.unit, self.l1_gas_consumed, self.l1_gas_price)?;
+        if self.l2_gas_consumed > 0 {
+            write!(f, ", l2 gas {} @ {}", self.l2_gas_consumed, self.l2_gas_price)?;
+        }
+        if self.l1_data_gas_consumed > 0 {
+            write!(f, ", l1 data gas {} @ {}", self.l1_data_gas_consumed, self.l1_data_gas_price)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Cairo `Uint256`: two 128-bit limbs. Serializes as a decimal string, since JSON
+/// numbers can't carry 256-bit values losslessly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U256 {
+    pub high: u128,
+    pub low: u128,
+}
+
+const DECIMAL_CHUNK: u128 = 10_000_000_000_000_000_000; // 10^19, fits in a u64
+
+impl U256 {
+    pub const ZERO: U256 = U256 { high: 0, low: 0 };
+
+    pub fn new(low: u128, high: u128) -> Self {
+        U256 { high, low }
+    }
+
+    /// The value as a u128, if the high limb is zero.
+    pub fn to_u128(self) -> Option<u128> {
+        (self.high == 0).then_some(self.low)
+    }
+
+    /// Parse hex digits (no `0x` prefix). None if empty, not hex, or over 256 bits.
+    fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.trim_start_matches('0');
+        if hex.is_empty() || digits.len() > 64 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let padded = format!("{:0>64}", digits);
+        Some(U256 {
+            high: u128::from_str_radix(&padded[..32], 16).ok()?,
+            low: u128::from_str_radix(&padded[32..], 16).ok()?,
+        })
+    }
+
+    /// Big-endian u64 limbs.
+    fn limbs(self) -> [u64; 4] {
+        [(self.high >> 64) as u64, self.high as u64, (self.low >> 64) as u64, self.low as u64]
+    }
+
+    fn from_limbs(l: [u64; 4]) -> Self {
+        U256 {
+            high: ((l[0] as u128) << 64) | l[1] as u128,
+            low: ((l[2] as u128) << 64) | l[3] as u128,
+        }
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(low: u128) -> Self {
+        U256 { high: 0, low }
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.high == 0 {
+            return write!(f, "{}", self.low);
+        }
+
+        // Repeated long division by 10^19 over the u64 limbs.
+        let mut limbs = self.limbs();
+        let mut chunks = Vec::new();
+        while limbs.iter().any(|&l| l != 0) {
+            let mut rem: u128 = 0;
+            for limb in limbs.iter_mut() {
+                let cur = (rem << 64) | *limb as u128;
+                *limb = (cur / DECIMAL_CHUNK) as u64;
+                rem = cur % DECIMAL_CHUNK;
+            }
+            chunks.push(rem as u64);
+        }
+
+        let mut chunks = chunks.into_iter().rev();
+        write!(f, "{}", chunks.next().unwrap_or(0))?;
+        for chunk in chunks {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for U256 {
+    type Err = anyhow::Error;
+
+    /// Parse a decimal string.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(anyhow::anyhow!("Invalid U256 decimal string: {:?}", s));
+        }
+
+        let mut limbs = [0u64; 4];
+        for digit in s.bytes().map(|b| (b - b'0') as u128) {
+            let mut carry = digit;
+            for limb in limbs.iter_mut().rev() {
+                let cur = *limb as u128 * 10 + carry;
+                *limb = cur as u64;
+                carry = cur >> 64;
+            }
+            if carry != 0 {
+                return Err(anyhow::anyhow!("U256 overflow: {}", s));
+            }
+        }
+        Ok(U256::from_limbs(limbs))
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Builds `FunctionCall` calldata in Cairo's serialization: a u256 is two felts
+/// `[low, high]` and an array is its length followed by the elements.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Calldata(Vec<FieldElement>);
+
+impl Calldata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_felt(&mut self, felt: FieldElement) -> &mut Self {
+        self.0.push(felt);
+        self
+    }
+
+    /// Emits the low limb, then the high limb.
+    pub fn push_u256(&mut self, value: U256) -> &mut Self {
+        self.0.extend([FieldElement::from(value.low), FieldElement::from(value.high)]);
+        self
+    }
+
+    /// Emits the length prefix, then the elements.
+    pub fn push_array(&mut self, elements: &[FieldElement]) -> &mut Self {
+        self.0.push(FieldElement::from(elements.len()));
+        self.0.extend_from_slice(elements);
+        self
+    }
+
+    /// Fails, leaving the calldata unchanged, if `address` isn't a valid felt.
+    pub fn push_address(&mut self, address: &str) -> Result<&mut Self> {
+        self.0.push(parse_address(address)?);
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<FieldElement> {
+        self.0
+    }
+}
+
+impl From<Calldata> for Vec<FieldElement> {
+    fn from(calldata: Calldata) -> Self {
+        calldata.0
+    }
+}
+
+/// Per-asteroid data from the game contracts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsteroidInfo {
+    pub id: u64,
+    pub name: String,
+    /// Owner address, canonical form (see `felt_to_address_string`).
+    pub owner: String,
+    /// Resource name -> abundance as a fraction (0.15 = 15%).
+    #[serde(default, alias = "resource_bonuses")]
+    pub resource_abundances: HashMap<String, f64>,
+}
+
+/// One asteroid read from a batch: the decoded info, or why it couldn't be read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AsteroidEntry {
+    Ok(AsteroidInfo),
+    Error { id: u64, error: String },
+}
+
+impl AsteroidEntry {
+    pub fn id(&self) -> u64 {
+        match self {
+            AsteroidEntry::Ok(info) => info.id,
+            AsteroidEntry::Error { id, .. } => *id,
+        }
+    }
+}
+
+/// One token balance read from a batch, or why it couldn't be read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BalanceEntry {
+    Ok {
+        /// As given in the request.
+        token: String,
+        /// In the token's base units.
+        balance: U256,
+        /// Present when the request set `include_metadata`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<TokenMetadata>,
+    },
+    Error { token: String, error: String },
+}
+
+/// What `StarknetClient::batch_query` should read. Fields other than `account`
+/// default to empty, so new reads can be added without breaking callers or JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchQueryRequest {
+    /// Account whose balances are read.
+    pub account: String,
+    /// Token addresses or `ContractRegistry` names such as `"eth"`.
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub asteroids: Vec<u64>,
+    /// Also read each token's name, symbol and decimals.
+    #[serde(default)]
+    pub include_metadata: bool,
+}
+
+/// Result of `StarknetClient::batch_query`. Serialize it yourself if you need JSON.
+/// Entries are in request order; a failed read doesn't fail the batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchQueryResult {
+    pub balances: Vec<BalanceEntry>,
+    pub asteroids: Vec<AsteroidEntry>,
+}
+
+/// ERC20 `name`/`symbol`/`decimals`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl fmt::Display for TokenMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {} decimals)", self.name, self.symbol, self.decimals)
+    }
+}
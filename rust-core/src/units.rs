@@ -0,0 +1,69 @@
+//! Integer-exact formatting of token amounts.
+
+use crate::types::U256;
+
+/// Render a raw base-unit amount as a decimal string, e.g. `1500000000000000000`
+/// with 18 decimals -> `"1.5"`. Trailing fractional zeros are trimmed. Done on the
+/// decimal string, so no float rounding is involved at any magnitude.
+pub fn format_balance(raw: impl Into<U256>, decimals: u8) -> String {
+    let digits = raw.into().to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, frac)
+    }
+}
+
+/// How `format_balance_rounded` treats digits beyond the display precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Drop them (round toward zero), so a balance is never overstated.
+    #[default]
+    Truncate,
+    /// Round to nearest, with exact halves going up.
+    HalfUp,
+}
+
+/// Render a raw amount with exactly `display_decimals` fractional digits, e.g.
+/// `1234567800000000000000` with 18 decimals at 2 places -> `"1234.56"` truncated,
+/// `"1234.57"` half-up. Zeros are kept so columns line up; with 0 places there
+/// is no decimal point. Works on the decimal string, like `format_balance`.
+pub fn format_balance_rounded(raw: impl Into<U256>, decimals: u8, display_decimals: u8, mode: RoundingMode) -> String {
+    let digits = raw.into().to_string();
+    let decimals = decimals as usize;
+    let display = display_decimals as usize;
+    let kept_decimals = display.min(decimals);
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (kept, dropped) = padded.split_at(padded.len() - decimals + kept_decimals);
+    let round_up = mode == RoundingMode::HalfUp && dropped.bytes().next().is_some_and(|d| d >= b'5');
+    let kept = if round_up { increment_decimal(kept) } else { kept.to_string() };
+
+    if display == 0 {
+        return kept;
+    }
+    let (whole, frac) = kept.split_at(kept.len() - kept_decimals);
+    format!("{}.{}{}", whole, frac, "0".repeat(display - kept_decimals))
+}
+
+/// Add one to a string of decimal digits, growing it on carry-out ("99" -> "100").
+fn increment_decimal(digits: &str) -> String {
+    let mut bytes = digits.as_bytes().to_vec();
+    for b in bytes.iter_mut().rev() {
+        if *b == b'9' {
+            *b = b'0';
+        } else {
+            *b += 1;
+            return String::from_utf8(bytes).expect("ascii digits");
+        }
+    }
+    format!("1{}", String::from_utf8(bytes).expect("ascii digits"))
+}
@@ -3,9 +3,6 @@ use aes_gcm::{
     Aes256Gcm, Key, Nonce
 };
 use anyhow::{Context, Result};
-use rand::RngCore;
-use std::fs;
-use std::path::Path;
 
 pub struct Vault {
     cipher: Aes256Gcm,
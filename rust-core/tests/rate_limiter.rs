@@ -0,0 +1,132 @@
+//! Rate limiter behavior under contention.
+
+use stark_pyrust_chain::rate_limiter::ApiRateLimiter;
+use std::time::{Duration, Instant};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn contended_waiters_have_bounded_wait() {
+    // 20/s with a burst of 20: 80 waiters drain in ~3s if nobody is starved.
+    let limiter = ApiRateLimiter::new(20).unwrap();
+    let started = Instant::now();
+
+    let handles: Vec<_> = (0..80)
+        .map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                limiter.check().await.unwrap();
+                started.elapsed()
+            })
+        })
+        .collect();
+
+    let mut worst = Duration::ZERO;
+    for handle in handles {
+        worst = worst.max(handle.await.unwrap());
+    }
+
+    assert!(worst < Duration::from_millis(4500), "worst wait {:?}", worst);
+}
+
+#[tokio::test]
+async fn estimated_wait_is_zero_until_burst_is_spent() {
+    // 5/s: burst of 5, then one permit every 200ms.
+    let limiter = ApiRateLimiter::new(5).unwrap();
+    assert_eq!(limiter.estimated_wait(), Duration::ZERO);
+
+    for _ in 0..4 {
+        limiter.check().await.unwrap();
+    }
+    assert_eq!(limiter.estimated_wait(), Duration::ZERO);
+
+    limiter.check().await.unwrap();
+    let wait = limiter.estimated_wait();
+    assert!(wait > Duration::from_millis(150) && wait <= Duration::from_millis(200), "wait {:?}", wait);
+
+    let started = Instant::now();
+    limiter.check().await.unwrap();
+    assert!(started.elapsed() + Duration::from_millis(50) >= wait);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn cancelled_waiters_release_their_place() {
+    // 10/s: drain the burst, then queue waiters and abandon them mid-wait.
+    let limiter = ApiRateLimiter::new(10).unwrap();
+    for _ in 0..10 {
+        limiter.check().await.unwrap();
+    }
+    for _ in 0..30 {
+        let _ = tokio::time::timeout(Duration::from_millis(5), limiter.check()).await;
+    }
+    let abandoned: Vec<_> = (0..30)
+        .map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.check().await })
+        })
+        .collect();
+    for handle in &abandoned {
+        handle.abort();
+    }
+    for handle in abandoned {
+        let _ = handle.await;
+    }
+
+    // Had the abandoned waits eaten permits, 5 more would take seconds, not ~0.5s.
+    let started = Instant::now();
+    for _ in 0..5 {
+        limiter.check().await.unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(900), "took {:?}", started.elapsed());
+    // At most about one interval; 30 leaked queue entries would add 3s. The slack
+    // absorbs scheduler delay when tests run in parallel.
+    let wait = limiter.estimated_wait();
+    assert!(wait <= Duration::from_millis(500), "wait {:?}", wait);
+}
+
+#[tokio::test]
+async fn priority_checks_use_the_reserve() {
+    // 10/s with 3 reserved: background gets a burst of 7, priority 3 more.
+    let limiter = ApiRateLimiter::with_reserve(10, 3).unwrap();
+    assert_eq!(limiter.requests_per_second(), 10);
+    assert_eq!(limiter.reserved(), 3);
+
+    for _ in 0..7 {
+        limiter.check().await.unwrap();
+    }
+    assert!(limiter.estimated_wait() > Duration::ZERO);
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        limiter.check_priority().await.unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(50), "priority waited {:?}", started.elapsed());
+
+    assert!(ApiRateLimiter::with_reserve(3, 3).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shutdown_releases_blocked_waiters() {
+    // 1/s: after the first permit, the next waits about a second.
+    let limiter = ApiRateLimiter::with_reserve(2, 1).unwrap();
+    limiter.check().await.unwrap();
+    limiter.check_priority().await.unwrap();
+
+    let waiters: Vec<_> = (0..3)
+        .map(|i| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                if i == 0 { limiter.check_priority().await } else { limiter.check().await }
+            })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    limiter.shutdown();
+    for waiter in waiters {
+        assert!(waiter.await.unwrap().is_err());
+    }
+    assert!(started.elapsed() < Duration::from_millis(200), "took {:?}", started.elapsed());
+
+    assert!(limiter.is_shut_down());
+    assert!(limiter.check().await.is_err());
+}
@@ -0,0 +1,1653 @@
+//! Integration tests against a fake JSON-RPC node (wiremock).
+//!
+//! These exercise the real HTTP transport and response decoding without a live
+//! Starknet endpoint.
+
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use stark_pyrust_chain::error::ClientError;
+use stark_pyrust_chain::reader::StarknetReader;
+use stark_pyrust_chain::starknet_client::StarknetClient;
+use std::sync::Arc;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ACCOUNT: &str = "0x0123";
+/// `SN_MAIN` as a hex felt, the way `starknet_chainId` answers.
+const SN_MAIN: &str = "0x534e5f4d41494e";
+
+/// Mount a canned `result` for every request whose JSON-RPC method is `rpc_method`.
+async fn mock_rpc(server: &MockServer, rpc_method: &str, result: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": result,
+        })))
+        .mount(server)
+        .await;
+}
+
+/// Mount a canned `result` for `starknet_call` requests whose call matches `call`.
+async fn mock_rpc_call(server: &MockServer, call: Value, result: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "starknet_call", "params": [call] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": result,
+        })))
+        .mount(server)
+        .await;
+}
+
+fn client_for(server: &MockServer) -> StarknetClient {
+    StarknetClient::new(Some(&server.uri())).expect("client against mock server")
+}
+
+fn latest_block(number: u64, price_in_wei: &str) -> Value {
+    latest_block_with_fri(number, price_in_wei, "0x0")
+}
+
+fn latest_block_with_fri(number: u64, price_in_wei: &str, price_in_fri: &str) -> Value {
+    json!({
+        "status": "ACCEPTED_ON_L2",
+        "block_hash": "0x1",
+        "parent_hash": "0x2",
+        "block_number": number,
+        "new_root": "0x3",
+        "timestamp": 1700000000u64,
+        "sequencer_address": "0x4",
+        "l1_gas_price": { "price_in_fri": price_in_fri, "price_in_wei": price_in_wei },
+        "starknet_version": "0.13.0",
+        "transactions": ["0x5", "0x6"],
+    })
+}
+
+fn pending_block(price_in_wei: &str) -> Value {
+    json!({
+        "parent_hash": "0x2",
+        "timestamp": 1700000000u64,
+        "sequencer_address": "0x4",
+        "l1_gas_price": { "price_in_fri": "0x0", "price_in_wei": price_in_wei },
+        "starknet_version": "0.13.0",
+        "transactions": [],
+    })
+}
+
+#[tokio::test]
+async fn network_status_decodes_latest_block() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block_with_fri(654321, "0x3b9aca00", "0x2540be400")).await;
+
+    let status = client_for(&server).get_network_status().await.unwrap();
+
+    assert_eq!(status.block_number, 654321);
+    assert_eq!(status.l1_gas_price_wei, 1_000_000_000);
+    assert_eq!(status.l1_gas_price_fri, 10_000_000_000);
+    assert!(!status.is_pending);
+}
+
+#[tokio::test]
+async fn network_status_reports_pending_block_as_zero() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", pending_block("0x64")).await;
+
+    let status = client_for(&server).get_network_status().await.unwrap();
+
+    assert_eq!(status.block_number, 0);
+    assert_eq!(status.l1_gas_price_wei, 100);
+    assert!(status.is_pending);
+}
+
+#[tokio::test]
+async fn eth_balance_decodes_low_limb() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0xde0b6b3a7640000", "0x0"])).await;
+
+    let balance = client_for(&server).get_eth_balance(ACCOUNT).await.unwrap();
+
+    assert_eq!(balance, 1_000_000_000_000_000_000);
+}
+
+#[tokio::test]
+async fn eth_balance_with_high_limb_is_an_error() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x5", "0x1"])).await;
+
+    let err = client_for(&server).get_eth_balance(ACCOUNT).await.unwrap_err();
+
+    match err.downcast_ref::<ClientError>() {
+        Some(ClientError::BalanceOverflow { balance }) => assert_eq!(balance.high, 1),
+        other => panic!("expected BalanceOverflow, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn out_of_range_uint256_limb_is_an_error() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x100000000000000000000000000000000", "0x0"])).await;
+
+    let err = client_for(&server).get_eth_balance(ACCOUNT).await.unwrap_err();
+
+    match err.downcast_ref::<ClientError>() {
+        Some(ClientError::UnexpectedResponse { entry_point, .. }) => assert_eq!(entry_point, "balanceOf"),
+        other => panic!("expected UnexpectedResponse, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn client_usable_as_trait_object() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x2a", "0x0"])).await;
+
+    let reader: Arc<dyn StarknetReader> = Arc::new(client_for(&server));
+
+    assert_eq!(reader.get_token_balance("0x5", ACCOUNT).await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn all_balances_keep_successes_when_one_token_fails() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "params": [{ "contract_address": "0xbad" }] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": 20, "message": "Contract not found" },
+        })))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_call", json!(["0x7", "0x0"])).await;
+
+    let balances = client_for(&server)
+        .get_all_balances(ACCOUNT, &["0x1", "0xbad", "0x2"])
+        .await;
+
+    assert_eq!(balances.len(), 3);
+    assert_eq!(balances[0].1.as_ref().unwrap(), &7);
+    assert!(balances[1].1.is_err());
+    assert_eq!(balances[2].1.as_ref().unwrap(), &7);
+}
+
+/// Whether the request body is a JSON-RPC batch (an array of requests).
+fn is_batch(request: &wiremock::Request) -> bool {
+    serde_json::from_slice::<Value>(&request.body).map(|body| body.is_array()).unwrap_or(false)
+}
+
+#[tokio::test]
+async fn all_balances_go_out_as_one_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(is_batch)
+        .respond_with(|request: &wiremock::Request| {
+            let calls: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            // Answer in reverse order, with the balance taken from the token address.
+            let responses: Vec<Value> = calls.iter().rev().map(|call| {
+                let token = &call["params"][0]["contract_address"];
+                match token.as_str() {
+                    Some("0xbad") => json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": 20, "message": "Contract not found" } }),
+                    _ => json!({ "jsonrpc": "2.0", "id": call["id"], "result": [token, "0x0"] }),
+                }
+            }).collect();
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let balances = client_for(&server)
+        .get_all_balances(ACCOUNT, &["0x1", "0xbad", "0x2"])
+        .await;
+
+    assert_eq!(balances.len(), 3);
+    assert_eq!(balances[0].1.as_ref().unwrap(), &1);
+    assert!(balances[1].1.is_err());
+    assert_eq!(balances[2].1.as_ref().unwrap(), &2);
+}
+
+#[tokio::test]
+async fn all_balances_fall_back_when_batches_are_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(is_batch)
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32600, "message": "Batch requests are not supported" },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_call", json!(["0x7", "0x0"])).await;
+
+    let balances = client_for(&server).get_all_balances(ACCOUNT, &["0x1", "0x2"]).await;
+
+    assert_eq!(balances[0].1.as_ref().unwrap(), &7);
+    assert_eq!(balances[1].1.as_ref().unwrap(), &7);
+}
+
+#[tokio::test]
+async fn storage_multi_batches_slots_aligned_to_keys() {
+    use starknet::core::types::FieldElement;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(is_batch)
+        .respond_with(|request: &wiremock::Request| {
+            let calls: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            // Echo each key back as its value, except slot 0xbad.
+            let responses: Vec<Value> = calls.iter().rev().map(|call| {
+                assert_eq!(call["method"], "starknet_getStorageAt");
+                match call["params"][1].as_str() {
+                    Some("0xbad") => json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": 20, "message": "Contract not found" } }),
+                    key => json!({ "jsonrpc": "2.0", "id": call["id"], "result": key }),
+                }
+            }).collect();
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let keys: Vec<FieldElement> = ["0x1", "0xbad", "0x2"].iter().map(|k| FieldElement::from_hex_be(k).unwrap()).collect();
+    let values = client_for(&server).get_storage_multi("0x5", &keys, None).await;
+
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[0].as_ref().unwrap(), &keys[0]);
+    assert!(matches!(values[1].as_ref().unwrap_err().downcast_ref::<ClientError>(), Some(ClientError::ContractNotFound)));
+    assert_eq!(values[2].as_ref().unwrap(), &keys[2]);
+}
+
+#[tokio::test]
+async fn priority_balance_skips_background_queue() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x9", "0x0"])).await;
+
+    // 4/s with half reserved: background has a burst of 2. The mock is local, so
+    // opt in to rate limiting.
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .requests_per_second(4)
+        .priority_reserve(0.5)
+        .rate_limit_local(true)
+        .build()
+        .unwrap();
+    client.get_eth_balance(ACCOUNT).await.unwrap();
+    client.get_eth_balance(ACCOUNT).await.unwrap();
+    assert!(client.estimated_wait() > Duration::ZERO);
+
+    let started = Instant::now();
+    assert_eq!(client.get_eth_balance_priority(ACCOUNT).await.unwrap(), 9);
+    assert!(started.elapsed() < Duration::from_millis(200), "priority read took {:?}", started.elapsed());
+    assert_eq!(client.total_rate_limit(), 4);
+
+    assert!(StarknetClient::builder().rpc_url(&server.uri()).priority_reserve(1.0).build().is_err());
+}
+
+#[tokio::test]
+async fn provider_escape_hatches_reach_the_node() {
+    use starknet::providers::Provider;
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_blockNumber", json!(321)).await;
+    let client = client_for(&server);
+
+    assert_eq!(client.raw_provider().unwrap().block_number().await.unwrap(), 321);
+    let head = client.with_provider(|p| async move { p.block_number().await }).await.unwrap();
+    assert_eq!(head, 321);
+    assert_eq!(client.metrics_snapshot().providers[0].requests, 1);
+
+    client.close().await;
+    assert!(client.raw_provider().is_err());
+}
+
+#[tokio::test]
+async fn reads_accept_typed_addresses() {
+    use stark_pyrust_chain::types::Address;
+    use starknet::core::types::FieldElement;
+
+    let server = MockServer::start().await;
+    mock_rpc_call(&server, json!({ "contract_address": "0x5", "calldata": ["0x123"] }), json!(["0x2a", "0x0"])).await;
+    let client = client_for(&server);
+
+    let token: Address = "0x5".parse().unwrap();
+    let owner = FieldElement::from(0x123u32);
+    assert_eq!(client.get_token_balance(token, owner).await.unwrap(), 42);
+    assert_eq!(client.get_token_balance("0x5", "0x0123").await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn rebuilt_provider_keeps_serving() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x7", "0x0"])).await;
+    let client = client_for(&server);
+
+    client.rebuild_provider(0).unwrap();
+    assert_eq!(client.get_eth_balance(ACCOUNT).await.unwrap(), 7);
+    assert!(client.rebuild_provider(1).is_err());
+
+    client.close().await;
+    assert!(client.rebuild_provider(0).is_err());
+}
+
+#[tokio::test]
+async fn eth_balance_short_response_is_an_error() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x5"])).await;
+
+    let err = client_for(&server).get_eth_balance(ACCOUNT).await.unwrap_err();
+
+    assert!(err.to_string().contains("expected 2 felts"), "{err}");
+    assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::UnexpectedResponse { .. })));
+}
+
+#[tokio::test]
+async fn checked_call_rejects_unexpected_length() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x5"])).await;
+    let client = client_for(&server);
+
+    let err = client.call_checked(ACCOUNT, "balanceOf", vec![], None, Some(2)).await.unwrap_err();
+    assert!(err.to_string().contains("expected 2 felts, got 1"));
+    assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::UnexpectedResponse { .. })));
+
+    assert_eq!(client.call_checked(ACCOUNT, "decimals", vec![], None, Some(1)).await.unwrap().len(), 1);
+    assert_eq!(client.call_checked(ACCOUNT, "name", vec![], None, None).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn starknet_id_resolves_both_ways() {
+    let server = MockServer::start().await;
+    let client = client_for(&server);
+
+    // domain_to_address([1, encode("ben")], [])
+    mock_rpc_call(&server, json!({ "calldata": ["0x1", "0x49ed", "0x0"] }), json!(["0x123"])).await;
+    assert_eq!(client.resolve_starknet_id("ben.stark").await.unwrap(), format!("0x{:0>64}", "123"));
+
+    server.reset().await;
+    mock_rpc(&server, "starknet_call", json!(["0x1", "0x49ed"])).await;
+    assert_eq!(client.address_to_name(ACCOUNT).await.unwrap(), "ben.stark");
+
+    server.reset().await;
+    mock_rpc(&server, "starknet_call", json!(["0x0"])).await;
+    assert!(client.resolve_starknet_id("nobody.stark").await.is_err());
+    assert!(client.address_to_name(ACCOUNT).await.is_err());
+}
+
+#[tokio::test]
+async fn rate_limited_response_honors_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(7, "0x1")).await;
+
+    let started = Instant::now();
+    let status = client_for(&server).get_network_status().await.unwrap();
+
+    assert_eq!(status.block_number, 7);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn custom_env_key_is_detected() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(9, "0x1")).await;
+    std::env::set_var("STARK_PYRUST_TEST_CUSTOM_RPC", server.uri());
+
+    let client = StarknetClient::builder()
+        .env_keys(&[])
+        .add_env_key("STARK_PYRUST_TEST_CUSTOM_RPC")
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get_network_status().await.unwrap().block_number, 9);
+}
+
+#[tokio::test]
+async fn asteroid_decodes_owner_name_and_abundances() {
+    let server = MockServer::start().await;
+    // owner, "Adalia Prime", 1 resource: ("Iron", 1500 bps)
+    mock_rpc(
+        &server,
+        "starknet_call",
+        json!(["0xabc", "0x4164616c6961205072696d65", "0x1", "0x49726f6e", "0x5dc"]),
+    )
+    .await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .asteroid_contract("0x99")
+        .build()
+        .unwrap();
+    let asteroid = client.get_asteroid(1).await.unwrap();
+
+    assert_eq!(asteroid.id, 1);
+    assert_eq!(asteroid.name, "Adalia Prime");
+    assert_eq!(asteroid.owner, format!("0x{:0>64}", "abc"));
+    assert_eq!(asteroid.resource_abundances["Iron"], 0.15);
+}
+
+#[tokio::test]
+async fn oversized_declared_lengths_are_rejected_not_overflowed() {
+    let server = MockServer::start().await;
+    // 2^63 resources, and a ByteArray of u64::MAX words: both would overflow usize.
+    mock_rpc(&server, "starknet_call", json!(["0xabc", "0x41", "0x8000000000000000", "0x1"])).await;
+    let client = StarknetClient::builder().rpc_url(&server.uri()).asteroid_contract("0x99").build().unwrap();
+
+    let err = client.get_asteroid(1).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("declared 9223372036854775808"), "{:#}", err);
+
+    // Valid decimals, so decoding reaches the oversized name and symbol.
+    server.reset().await;
+    let decimals = format!("{:#x}", starknet::core::utils::get_selector_from_name("decimals").unwrap());
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "starknet_call", "params": [{ "entry_point_selector": decimals }] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": ["0x12"] })))
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_call", json!(["0xffffffffffffffff", "0x41", "0x1"])).await;
+    let err = client.get_token_metadata("0x5").await.unwrap_err();
+    assert!(format!("{:#}", err).contains("Malformed ByteArray"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn asteroid_requires_configured_contract() {
+    let server = MockServer::start().await;
+
+    assert!(client_for(&server).get_asteroid(1).await.is_err());
+}
+
+#[tokio::test]
+async fn close_rejects_later_requests() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+
+    let client = client_for(&server);
+    client.get_network_status().await.unwrap();
+    client.close().await;
+
+    assert!(client.is_closed());
+    let err = client.get_network_status().await.unwrap_err();
+    assert!(err.to_string().contains("client closed"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn close_releases_requests_waiting_on_the_rate_limiter() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    // 1/s: after the first request, the next waits about a second for a permit.
+    let client = Arc::new(
+        StarknetClient::builder()
+            .rpc_url(&server.uri())
+            .rate_limit_local(true)
+            .requests_per_second(1)
+            .build()
+            .unwrap(),
+    );
+    client.get_network_status().await.unwrap();
+
+    let waiting = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_network_status().await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    client.close().await;
+    assert!(started.elapsed() < Duration::from_millis(500), "close took {:?}", started.elapsed());
+    let err = waiting.await.unwrap().unwrap_err();
+    assert!(err.to_string().contains("closed"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn provider_states_track_failures_and_recovery() {
+    use stark_pyrust_chain::starknet_client::BreakerState;
+
+    let server = MockServer::start().await;
+    mock_rpc_error(&server, json!({ "code": 24, "message": "Block not found" })).await;
+    let client = client_for(&server);
+
+    assert_eq!(client.provider_states()[0].state, BreakerState::Closed);
+    client.get_network_status().await.unwrap_err();
+    let state = &client.provider_states()[0];
+    assert_eq!(state.state, BreakerState::HalfOpen);
+    assert_eq!(state.consecutive_failures, 1);
+    assert!(state.last_error.as_deref().unwrap().contains("Block not found"));
+    assert!(!state.url.is_empty());
+
+    server.reset().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    client.get_network_status().await.unwrap();
+    let state = &client.provider_states()[0];
+    assert_eq!((state.state, state.consecutive_failures), (BreakerState::Closed, 0));
+
+    client.close().await;
+    assert_eq!(client.provider_states()[0].state, BreakerState::Open);
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn gzip_response_is_decompressed() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use wiremock::matchers::header;
+
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "result": latest_block(77, "0x1") });
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.to_string().as_bytes()).unwrap();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("accept-encoding", "gzip"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .insert_header("Content-Type", "application/json")
+                .set_body_bytes(encoder.finish().unwrap()),
+        )
+        .mount(&server)
+        .await;
+
+    let status = client_for(&server).get_network_status().await.unwrap();
+
+    assert_eq!(status.block_number, 77);
+}
+
+#[tokio::test]
+async fn rpc_error_surfaces_as_err() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": 24, "message": "Block not found" },
+        })))
+        .mount(&server)
+        .await;
+
+    assert!(client_for(&server).get_network_status().await.is_err());
+}
+
+/// Mount a JSON-RPC error object as the answer to every request.
+async fn mock_rpc_error(server: &MockServer, error: Value) {
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": error,
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn rpc_error_codes_map_to_client_errors() {
+    let cases = [
+        (json!({ "code": 20, "message": "Contract not found" }), ClientError::ContractNotFound),
+        (json!({ "code": 22, "message": "Invalid call data" }), ClientError::InvalidCallData),
+        (json!({ "code": 24, "message": "Block not found" }), ClientError::BlockNotFound),
+        (
+            json!({ "code": 40, "message": "Contract error", "data": { "revert_error": "Insufficient balance" } }),
+            ClientError::ContractError { reason: "Insufficient balance".to_string() },
+        ),
+        (
+            json!({ "code": 40, "message": "Contract error" }),
+            ClientError::ContractError { reason: "Contract error".to_string() },
+        ),
+    ];
+
+    for (error, expected) in cases {
+        let server = MockServer::start().await;
+        mock_rpc_error(&server, error).await;
+
+        let err = client_for(&server).call("0x5", "balanceOf", vec![]).await.unwrap_err();
+
+        assert_eq!(err.downcast_ref::<ClientError>(), Some(&expected), "{}", err);
+        assert!(err.to_string().starts_with("Call to balanceOf failed"), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn block_not_found_survives_wrapping() {
+    let server = MockServer::start().await;
+    mock_rpc_error(&server, json!({ "code": 24, "message": "Block not found" })).await;
+
+    let err = client_for(&server).get_network_status().await.unwrap_err();
+
+    assert_eq!(err.downcast_ref::<ClientError>(), Some(&ClientError::BlockNotFound));
+}
+
+#[tokio::test]
+async fn unknown_rpc_error_code_stays_untyped() {
+    let server = MockServer::start().await;
+    mock_rpc_error(&server, json!({ "code": 10, "message": "No trace available" })).await;
+
+    let err = client_for(&server).get_token_balance("0x5", ACCOUNT).await.unwrap_err();
+
+    assert!(err.downcast_ref::<ClientError>().is_none());
+    assert!(err.to_string().contains("No trace available"), "{}", err);
+}
+
+#[tokio::test]
+async fn l1_accepted_status_finds_newest_l1_block() {
+    let server = MockServer::start().await;
+    // Head 1000; blocks up to 937 have reached L1.
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "starknet_getBlockWithTxHashes" })))
+        .respond_with(|request: &wiremock::Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let number = body["params"][0]["block_number"].as_u64().unwrap_or(1000);
+            let mut block = latest_block(number, "0x1");
+            block["status"] = json!(if number <= 937 { "ACCEPTED_ON_L1" } else { "ACCEPTED_ON_L2" });
+            ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": block }))
+        })
+        .mount(&server)
+        .await;
+
+    let client = StarknetClient::builder().rpc_url(&server.uri()).requests_per_second(100).build().unwrap();
+    let status = client.get_l1_accepted_status().await.unwrap();
+
+    assert_eq!(status.block_number, 937);
+    assert!(server.received_requests().await.unwrap().len() < 20);
+}
+
+/// Mount `responses` (JSON-RPC `result` or `error` members) to be served in order,
+/// the last one repeating.
+async fn mock_rpc_sequence(server: &MockServer, rpc_method: &str, responses: Vec<Value>) {
+    let count = responses.len();
+    for (i, response) in responses.into_iter().enumerate() {
+        let mut body = json!({ "jsonrpc": "2.0", "id": 1 });
+        body.as_object_mut().unwrap().extend(response.as_object().unwrap().clone());
+        let mock = Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .with_priority(i as u8 + 1);
+        let mock = if i + 1 < count { mock.up_to_n_times(1) } else { mock };
+        mock.mount(server).await;
+    }
+}
+
+fn tx_poll_client(server: &MockServer) -> StarknetClient {
+    StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .tx_poll_interval(Duration::from_millis(5))
+        .requests_per_second(100)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn wait_for_acceptance_reports_each_transition() {
+    use stark_pyrust_chain::types::TxStatus;
+    use starknet::core::types::FieldElement;
+
+    let server = MockServer::start().await;
+    mock_rpc_sequence(&server, "starknet_getTransactionStatus", vec![
+        json!({ "error": { "code": 29, "message": "Transaction hash not found" } }),
+        json!({ "result": { "finality_status": "RECEIVED" } }),
+        json!({ "result": { "finality_status": "RECEIVED" } }),
+        json!({ "result": { "finality_status": "ACCEPTED_ON_L2", "execution_status": "SUCCEEDED" } }),
+    ]).await;
+
+    let mut seen = Vec::new();
+    let status = tx_poll_client(&server)
+        .wait_for_acceptance(FieldElement::from(0xabcu32), TxStatus::AcceptedOnL2, Duration::from_secs(5), |s| seen.push(s))
+        .await
+        .unwrap();
+
+    assert_eq!(status, TxStatus::AcceptedOnL2);
+    assert_eq!(seen, [TxStatus::Received, TxStatus::AcceptedOnL2]);
+}
+
+#[tokio::test]
+async fn wait_for_acceptance_distinguishes_revert_and_rejection() {
+    use stark_pyrust_chain::types::TxStatus;
+    use starknet::core::types::FieldElement;
+
+    let hash = FieldElement::from(0xabcu32);
+    for (result, expected) in [
+        (
+            json!({ "finality_status": "ACCEPTED_ON_L2", "execution_status": "REVERTED" }),
+            ClientError::TransactionReverted { hash },
+        ),
+        (json!({ "finality_status": "REJECTED" }), ClientError::TransactionRejected { hash }),
+    ] {
+        let server = MockServer::start().await;
+        mock_rpc(&server, "starknet_getTransactionStatus", result).await;
+
+        let err = tx_poll_client(&server)
+            .wait_for_acceptance(hash, TxStatus::AcceptedOnL2, Duration::from_secs(5), |_| {})
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.downcast_ref::<ClientError>(), Some(&expected));
+    }
+}
+
+#[tokio::test]
+async fn metrics_survive_restart_via_seed() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+
+    let first = client_for(&server);
+    first.get_network_status().await.unwrap();
+    first.get_network_status().await.unwrap();
+    let saved = serde_json::to_string(&first.metrics_snapshot()).unwrap();
+
+    let restored = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .seed_metrics(serde_json::from_str(&saved).unwrap())
+        .build()
+        .unwrap();
+    restored.get_network_status().await.unwrap();
+
+    let snapshot = restored.metrics_snapshot();
+    assert_eq!(snapshot.providers[0].requests, 3);
+    assert_eq!(snapshot.providers[0].failures, 0);
+}
+
+#[tokio::test]
+async fn nonce_defaults_to_pending_block() {
+    use wiremock::matchers::body_string_contains;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "starknet_getNonce" })))
+        .and(body_string_contains("\"pending\""))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": "0x3",
+        })))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client_for(&server).get_nonce(ACCOUNT).await.unwrap(), "3");
+}
+
+#[tokio::test]
+async fn single_provider_waits_out_throttle_under_any_selection() {
+    use stark_pyrust_chain::starknet_client::Selection;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(8, "0x1")).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .selection(Selection::LowestLatency)
+        .build()
+        .unwrap();
+    let started = Instant::now();
+
+    assert_eq!(client.get_network_status().await.unwrap().block_number, 8);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(client.metrics_snapshot().providers[0].retries, 1);
+}
+
+#[tokio::test]
+async fn request_timeout_bounds_slow_responses() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": latest_block(3, "0x1") }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&server)
+        .await;
+    let client = |timeout| {
+        StarknetClient::builder()
+            .rpc_url(&server.uri())
+            .connect_timeout(Duration::from_secs(1))
+            .request_timeout(timeout)
+            .build()
+            .unwrap()
+    };
+
+    assert!(client(Duration::from_millis(50)).get_network_status().await.is_err());
+    assert_eq!(client(Duration::from_secs(5)).get_network_status().await.unwrap().block_number, 3);
+    assert!(StarknetClient::builder().rpc_url(&server.uri()).connect_timeout(Duration::ZERO).build().is_err());
+}
+
+#[tokio::test]
+async fn token_balance_can_read_pending_block() {
+    use starknet::core::types::{BlockId, BlockTag};
+    use wiremock::matchers::body_string_contains;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("\"pending\""))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": ["0x9", "0x0"],
+        })))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_call", json!(["0x8", "0x0"])).await;
+
+    let client = client_for(&server);
+    let pending = Some(BlockId::Tag(BlockTag::Pending));
+
+    assert_eq!(client.get_eth_balance(ACCOUNT).await.unwrap(), 8);
+    assert_eq!(client.get_eth_balance_at(ACCOUNT, pending).await.unwrap(), 9);
+}
+
+#[tokio::test]
+async fn user_agent_defaults_and_can_be_overridden() {
+    use stark_pyrust_chain::transport::DEFAULT_USER_AGENT;
+    use wiremock::matchers::header;
+
+    let server = MockServer::start().await;
+    for agent in [DEFAULT_USER_AGENT, "my-bot/2.0"] {
+        Mock::given(method("POST"))
+            .and(header("user-agent", agent))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": latest_block(3, "0x1"),
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    client_for(&server).get_network_status().await.unwrap();
+    StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .user_agent("my-bot/2.0")
+        .build()
+        .unwrap()
+        .get_network_status()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn construction_mode_decides_invalid_url_handling() {
+    use stark_pyrust_chain::starknet_client::ConstructionMode;
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(4, "0x1")).await;
+    let builder = || StarknetClient::builder().rpc_url("not a url").rpc_url(&server.uri());
+
+    let err = builder().construction(ConstructionMode::Strict).build().err().unwrap();
+    assert!(err.to_string().contains("not a url"));
+
+    let client = builder().construction(ConstructionMode::Lenient).build().unwrap();
+    assert_eq!(client.metrics_snapshot().providers.len(), 1);
+    assert_eq!(client.get_network_status().await.unwrap().block_number, 4);
+
+    let only_invalid = StarknetClient::builder().rpc_url("not a url").build();
+    assert!(only_invalid.is_err());
+}
+
+#[tokio::test]
+async fn lagging_provider_is_reported_and_avoided() {
+    let ahead = MockServer::start().await;
+    let behind = MockServer::start().await;
+    mock_rpc(&ahead, "starknet_getBlockWithTxHashes", latest_block(100, "0x1")).await;
+    mock_rpc(&behind, "starknet_getBlockWithTxHashes", latest_block(40, "0x1")).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&ahead.uri())
+        .rpc_url(&behind.uri())
+        .build()
+        .unwrap();
+    // Round-robin: one status read from each provider.
+    client.get_network_status().await.unwrap();
+    client.get_network_status().await.unwrap();
+
+    let lag: Vec<Option<u64>> = client.provider_head_lag().into_iter().map(|(_, l)| l).collect();
+    assert_eq!(lag, [Some(0), Some(60)]);
+
+    for _ in 0..4 {
+        assert_eq!(client.get_network_status().await.unwrap().block_number, 100);
+    }
+    assert_eq!(client.metrics_snapshot().providers[1].requests, 1);
+}
+
+#[tokio::test]
+async fn execute_with_session_estimates_signs_and_submits() {
+    use starknet::accounts::Call;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::get_selector_from_name;
+    use stark_pyrust_chain::session_keys::{SessionKey, SessionPolicy};
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_chainId", json!(SN_MAIN)).await;
+    mock_rpc(&server, "starknet_getNonce", json!("0x4")).await;
+    mock_rpc(
+        &server,
+        "starknet_estimateFee",
+        json!([{ "gas_consumed": "0xa", "gas_price": "0x64", "overall_fee": "0x3e8", "unit": "WEI" }]),
+    )
+    .await;
+    Mock::given(method("POST"))
+        // 1000 estimated + 10%
+        .and(body_partial_json(json!({
+            "method": "starknet_addInvokeTransaction",
+            "params": [{ "max_fee": "0x44c", "nonce": "0x4", "version": "0x1" }],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "transaction_hash": "0xabc" },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let session = SessionKey::generate_with_ttl(3600).unwrap().with_policy(SessionPolicy {
+        allowed_contracts: vec!["0x55".to_string()],
+        allowed_selectors: vec!["start_extraction".to_string()],
+    });
+    let call = |selector: &str| Call {
+        to: FieldElement::from(0x55u32),
+        selector: get_selector_from_name(selector).unwrap(),
+        calldata: vec![FieldElement::ONE],
+    };
+    let client = client_for(&server);
+
+    let hash = client.execute_with_session(&session, ACCOUNT, vec![call("start_extraction")]).await.unwrap();
+    assert_eq!(hash, FieldElement::from(0xabcu32));
+
+    let err = client.execute_with_session(&session, ACCOUNT, vec![call("transfer")]).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::CallNotPermitted { .. })));
+
+    let mut expired = SessionKey::generate().unwrap();
+    expired.expires_at = 1;
+    let err = client.execute_with_session(&expired, ACCOUNT, vec![call("start_extraction")]).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::SessionExpired { .. })));
+}
+
+#[tokio::test]
+async fn execute_with_session_checks_expiry_on_the_client_clock() {
+    use starknet::accounts::Call;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::get_selector_from_name;
+    use stark_pyrust_chain::session_keys::{MockClock, SessionKey};
+
+    let server = MockServer::start().await;
+    let clock = Arc::new(MockClock::new(1_700_000_000));
+    let client = StarknetClient::builder().rpc_url(&server.uri()).clock(clock.clone()).build().unwrap();
+    let session = SessionKey::generate_with_ttl_on(clock.as_ref(), 60).unwrap();
+    let call = Call {
+        to: FieldElement::from(0x55u32),
+        selector: get_selector_from_name("start_extraction").unwrap(),
+        calldata: vec![],
+    };
+
+    clock.advance(60);
+    let err = client.execute_with_session(&session, ACCOUNT, vec![call]).await.unwrap_err();
+
+    match err.downcast_ref::<ClientError>() {
+        Some(ClientError::SessionExpired { expires_at }) => assert_eq!(*expires_at, 1_700_000_060),
+        other => panic!("expected SessionExpired, got {:?}", other),
+    }
+    // Rejected before any request was made.
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn execute_with_session_signs_for_the_chain_the_node_reports() {
+    use starknet::accounts::Call;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::get_selector_from_name;
+    use stark_pyrust_chain::network::Network;
+    use stark_pyrust_chain::session_keys::SessionKey;
+
+    let server = MockServer::start().await;
+    // "SN_SEPOLIA"
+    mock_rpc(&server, "starknet_chainId", json!("0x534e5f5345504f4c4941")).await;
+    let session = SessionKey::generate().unwrap();
+    let call = || Call {
+        to: FieldElement::from(0x55u32),
+        selector: get_selector_from_name("start_extraction").unwrap(),
+        calldata: vec![],
+    };
+
+    // Defaulted to mainnet, but the node is on sepolia: refuse before signing.
+    let implicit = client_for(&server);
+    let err = implicit.execute_with_session(&session, ACCOUNT, vec![call()]).await.unwrap_err();
+    assert!(err.to_string().contains("SN_SEPOLIA"), "{:#}", err);
+    assert!(implicit.execute_with_session(&session, ACCOUNT, vec![call()]).await.is_err());
+    assert_eq!(chain_id_requests(&server).await, 1, "chain id is cached");
+
+    // An explicit network is trusted without asking; this one fails later, on the nonce.
+    let explicit = StarknetClient::builder().rpc_url(&server.uri()).network(Network::Sepolia).build().unwrap();
+    let err = explicit.execute_with_session(&session, ACCOUNT, vec![call()]).await.unwrap_err();
+    assert!(err.to_string().contains("nonce"), "{:#}", err);
+    assert_eq!(chain_id_requests(&server).await, 1);
+}
+
+async fn chain_id_requests(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap().iter()
+        .filter(|request| String::from_utf8_lossy(&request.body).contains("starknet_chainId"))
+        .count()
+}
+
+#[tokio::test]
+async fn session_policy_resolves_registry_names() {
+    use starknet::accounts::Call;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::get_selector_from_name;
+    use stark_pyrust_chain::network::ContractRegistry;
+    use stark_pyrust_chain::session_keys::{SessionKey, SessionPolicy};
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_chainId", json!(SN_MAIN)).await;
+    mock_rpc(&server, "starknet_getNonce", json!("0x4")).await;
+    let session = SessionKey::generate().unwrap().with_policy(SessionPolicy {
+        allowed_contracts: vec![ContractRegistry::INTERACT.to_string()],
+        allowed_selectors: vec![],
+    });
+    let call = |to: u32| Call {
+        to: FieldElement::from(to),
+        selector: get_selector_from_name("start_extraction").unwrap(),
+        calldata: vec![],
+    };
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .register_contract(ContractRegistry::INTERACT, "0x55")
+        .build()
+        .unwrap();
+
+    let err = client.execute_with_session(&session, ACCOUNT, vec![call(0x56)]).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::CallNotPermitted { .. })));
+
+    // Permitted, so it gets as far as fee estimation, which isn't mocked.
+    let err = client.execute_with_session(&session, ACCOUNT, vec![call(0x55)]).await.unwrap_err();
+    assert!(err.to_string().contains("Fee estimation failed"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn class_hash_and_sierra_class_decode() {
+    use starknet::core::types::{ContractClass, FieldElement};
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getClassHashAt", json!("0x77")).await;
+    mock_rpc(
+        &server,
+        "starknet_getClass",
+        json!({
+            "sierra_program": ["0x1"],
+            "contract_class_version": "0.1.0",
+            "entry_points_by_type": { "CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": [] },
+            "abi": "[]",
+        }),
+    )
+    .await;
+
+    let client = client_for(&server);
+    let class_hash = client.get_class_hash_at("0x99", None).await.unwrap();
+    assert_eq!(class_hash, FieldElement::from(0x77u32));
+
+    match client.get_class(class_hash).await.unwrap() {
+        ContractClass::Sierra(class) => assert_eq!(class.abi, "[]"),
+        ContractClass::Legacy(_) => panic!("expected a Sierra class"),
+    }
+}
+
+#[tokio::test]
+async fn block_stream_polls_with_jitter() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": latest_block(10, "0x1"),
+        })))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(11, "0x1")).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .block_poll_interval(Duration::from_millis(10))
+        .block_poll_jitter(0.5)
+        .requests_per_second(100)
+        .build()
+        .unwrap();
+
+    let blocks = client.collect_blocks(1, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(blocks[0].block_number, 11);
+
+    for jitter in [-0.1, 1.5, f64::NAN] {
+        assert!(StarknetClient::builder().rpc_url(&server.uri()).block_poll_jitter(jitter).build().is_err());
+    }
+}
+
+#[tokio::test]
+async fn block_stream_ignores_pending_blocks() {
+    let server = MockServer::start().await;
+    // Pending, head 5 (baseline), pending twice, head 6, then pending forever.
+    let sequence = [pending_block("0x1"), latest_block(5, "0x1"), pending_block("0x1"), pending_block("0x1"), latest_block(6, "0x1")];
+    for (priority, block) in sequence.into_iter().enumerate() {
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": block,
+            })))
+            .up_to_n_times(1)
+            .with_priority(priority as u8 + 1)
+            .mount(&server)
+            .await;
+    }
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", pending_block("0x1")).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .block_poll_interval(Duration::from_millis(10))
+        .requests_per_second(100)
+        .build()
+        .unwrap();
+
+    let blocks = client.collect_blocks(3, Duration::from_millis(500)).await.unwrap();
+    let numbers: Vec<u64> = blocks.iter().map(|b| b.block_number).collect();
+    assert_eq!(numbers, [6]);
+    assert!(blocks.iter().all(|b| !b.is_pending));
+}
+
+#[tokio::test]
+async fn collect_blocks_stops_at_count_or_timeout() {
+    let server = MockServer::start().await;
+    // Head 10 at subscription, then 11, then 12 forever.
+    for (priority, number) in [(1u8, 10u64), (2, 11)] {
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": latest_block(number, "0x1"),
+            })))
+            .up_to_n_times(1)
+            .with_priority(priority)
+            .mount(&server)
+            .await;
+    }
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(12, "0x1")).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .block_poll_interval(Duration::from_millis(10))
+        .requests_per_second(100)
+        .build()
+        .unwrap();
+
+    let blocks = client.collect_blocks(2, Duration::from_secs(5)).await.unwrap();
+    let numbers: Vec<u64> = blocks.iter().map(|b| b.block_number).collect();
+    assert_eq!(numbers, [11, 12]);
+
+    // Head stays at 12: nothing new arrives before the timeout.
+    let started = Instant::now();
+    assert!(client.collect_blocks(1, Duration::from_millis(200)).await.unwrap().is_empty());
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn block_transactions_decode_type_sender_and_calldata() {
+    use starknet::core::types::{BlockId, BlockTag, FieldElement};
+    use stark_pyrust_chain::types::TransactionType;
+
+    let server = MockServer::start().await;
+    let mut block = latest_block(5, "0x1");
+    block["transactions"] = json!([{
+        "type": "INVOKE",
+        "version": "0x1",
+        "transaction_hash": "0xaa",
+        "sender_address": "0x123",
+        "calldata": ["0x1", "0x2"],
+        "max_fee": "0x0",
+        "signature": [],
+        "nonce": "0x0",
+    }]);
+    mock_rpc(&server, "starknet_getBlockWithTxs", block).await;
+
+    let txs = client_for(&server).get_block_transactions(BlockId::Number(5)).await.unwrap();
+
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].tx_type, TransactionType::Invoke);
+    assert_eq!(txs[0].hash, format!("0x{:0>64}", "aa"));
+    assert_eq!(txs[0].sender.as_deref(), Some(format!("0x{:0>64}", "123").as_str()));
+    assert_eq!(txs[0].calldata, [FieldElement::ONE, FieldElement::TWO]);
+
+    let pending = MockServer::start().await;
+    mock_rpc(&pending, "starknet_getBlockWithTxs", pending_block("0x1")).await;
+    let txs = client_for(&pending).get_block_transactions(BlockId::Tag(BlockTag::Pending)).await.unwrap();
+    assert!(txs.is_empty());
+}
+
+#[test]
+fn blocking_handle_is_shared_and_reusable_from_sync_code() {
+    let handle = StarknetClient::builder().rpc_url("http://127.0.0.1:1").build().unwrap().blocking_handle();
+    let server = handle.block_on(async {
+        let server = MockServer::start().await;
+        mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(5, "0x1")).await;
+        server
+    });
+
+    let client = client_for(&server);
+    for _ in 0..3 {
+        assert_eq!(client.blocking_handle().block_on(client.get_network_status()).unwrap().block_number, 5);
+    }
+
+    let own = tokio::runtime::Runtime::new().unwrap();
+    let injected = StarknetClient::builder().rpc_url(&server.uri()).runtime_handle(own.handle().clone()).build().unwrap();
+    assert_eq!(injected.blocking_handle().block_on(injected.get_network_status()).unwrap().block_number, 5);
+}
+
+#[tokio::test]
+async fn inspector_sees_raw_requests_and_responses() {
+    use std::sync::Mutex;
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(4, "0x1")).await;
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .inspector(move |method, request, response| {
+            log.lock().unwrap().push((method.to_string(), request.clone(), response.clone()));
+        })
+        .build()
+        .unwrap();
+    client.get_network_status().await.unwrap();
+
+    let seen = seen.lock().unwrap();
+    let (method, request, response) = &seen[0];
+    assert_eq!(method, "starknet_getBlockWithTxHashes");
+    assert_eq!(request["method"], "starknet_getBlockWithTxHashes");
+    assert_eq!(response["result"]["block_number"], 4);
+}
+
+#[tokio::test]
+async fn block_transaction_count_reads_count_only() {
+    use starknet::core::types::BlockId;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "starknet_getBlockTransactionCount", "params": [{ "block_number": 9 }] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": 17 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(client_for(&server).get_block_transaction_count(BlockId::Number(9)).await.unwrap(), 17);
+}
+
+#[tokio::test]
+async fn connect_drops_unreachable_providers() {
+    let live = MockServer::start().await;
+    let dead = MockServer::start().await; // no mocks: every request 404s
+    mock_rpc(&live, "starknet_blockNumber", json!(12)).await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&dead.uri())
+        .rpc_url(&live.uri())
+        .connect()
+        .await
+        .unwrap();
+
+    let providers = client.metrics_snapshot().providers;
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0].url, live.uri());
+
+    let err = StarknetClient::connect(Some(&dead.uri())).await.err().unwrap();
+    assert!(err.to_string().contains("No RPC provider responded"));
+}
+
+#[tokio::test]
+async fn nft_balance_and_owned_token_ids() {
+    use starknet::core::utils::get_selector_from_name;
+
+    let server = MockServer::start().await;
+    let selector = |name: &str| format!("{:#x}", get_selector_from_name(name).unwrap());
+    mock_rpc_call(&server, json!({ "entry_point_selector": selector("balanceOf") }), json!(["0x2"])).await;
+    // Index and id are both Uint256: [low, high].
+    for (index, id) in [("0x0", "0x65"), ("0x1", "0x3e9")] {
+        mock_rpc_call(
+            &server,
+            json!({ "entry_point_selector": selector("tokenOfOwnerByIndex"), "calldata": ["0x123", index, "0x0"] }),
+            json!([id, "0x0"]),
+        )
+        .await;
+    }
+
+    let client = client_for(&server);
+
+    assert_eq!(client.get_nft_balance("0x77", "0x123").await.unwrap(), 2);
+    assert_eq!(client.get_owned_token_ids("0x77", "0x123").await.unwrap(), [101, 1001]);
+}
+
+#[tokio::test]
+async fn owned_token_ids_reject_single_felt_and_oversized_ids() {
+    use starknet::core::utils::get_selector_from_name;
+
+    let selector = |name: &str| format!("{:#x}", get_selector_from_name(name).unwrap());
+    for id in [json!(["0x65"]), json!(["0x65", "0x1"])] {
+        let server = MockServer::start().await;
+        mock_rpc_call(&server, json!({ "entry_point_selector": selector("balanceOf") }), json!(["0x1"])).await;
+        mock_rpc_call(&server, json!({ "entry_point_selector": selector("tokenOfOwnerByIndex") }), id).await;
+
+        let err = client_for(&server).get_owned_token_ids("0x77", "0x123").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ClientError>(), Some(ClientError::UnexpectedResponse { .. })), "{err}");
+    }
+}
+
+#[test]
+fn detection_report_names_each_key_without_values() {
+    use stark_pyrust_chain::starknet_client::EnvKeyStatus;
+
+    std::env::set_var("STARK_PYRUST_TEST_REPORT_EMPTY", "  ");
+    std::env::set_var("STARK_PYRUST_TEST_REPORT_BAD", "secret-key-not-a-url");
+    std::env::set_var("STARK_PYRUST_TEST_REPORT_OK", "https://rpc.example/v0_6/secret");
+    let keys = [
+        "STARK_PYRUST_TEST_REPORT_UNSET",
+        "STARK_PYRUST_TEST_REPORT_EMPTY",
+        "STARK_PYRUST_TEST_REPORT_BAD",
+        "STARK_PYRUST_TEST_REPORT_OK",
+    ];
+
+    let report = StarknetClient::builder().env_keys(&keys).detection_report();
+    let statuses: Vec<EnvKeyStatus> = report.entries.iter().map(|(_, s)| *s).collect();
+    assert_eq!(
+        statuses,
+        [EnvKeyStatus::Unset, EnvKeyStatus::Empty, EnvKeyStatus::InvalidUrl, EnvKeyStatus::Accepted]
+    );
+
+    let err = StarknetClient::builder().env_keys(&keys[..3]).build().err().unwrap().to_string();
+    assert!(err.contains("STARK_PYRUST_TEST_REPORT_BAD=invalid-url"), "{}", err);
+    assert!(!err.contains("secret"), "{}", err);
+}
+
+#[tokio::test]
+async fn local_endpoints_skip_rate_limit_unless_asked() {
+    let server = MockServer::start().await; // listens on 127.0.0.1
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    let builder = || StarknetClient::builder().rpc_url(&server.uri()).requests_per_second(1);
+
+    let unlimited = builder().build().unwrap();
+    let started = Instant::now();
+    for _ in 0..5 {
+        unlimited.get_network_status().await.unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(900));
+
+    let limited = builder().rate_limit_local(true).build().unwrap();
+    let started = Instant::now();
+    for _ in 0..3 {
+        limited.get_network_status().await.unwrap();
+    }
+    assert!(started.elapsed() >= Duration::from_millis(1900));
+}
+
+#[tokio::test]
+async fn max_concurrency_serializes_excess_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": latest_block(1, "0x1") }))
+                .set_delay(Duration::from_millis(200)),
+        )
+        .mount(&server)
+        .await;
+    let timed = |client: StarknetClient| async move {
+        let started = Instant::now();
+        let (a, b, c) = tokio::join!(
+            client.get_network_status(),
+            client.get_network_status(),
+            client.get_network_status()
+        );
+        a.unwrap();
+        b.unwrap();
+        c.unwrap();
+        started.elapsed()
+    };
+
+    assert!(timed(client_for(&server)).await < Duration::from_millis(500));
+    let one_at_a_time = StarknetClient::builder().rpc_url(&server.uri()).max_concurrency(1).build().unwrap();
+    assert!(timed(one_at_a_time).await >= Duration::from_millis(600));
+}
+
+#[tokio::test]
+async fn batch_query_reads_balances_and_asteroids_and_isolates_failures() {
+    use stark_pyrust_chain::types::{AsteroidEntry, BalanceEntry, BatchQueryRequest};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(is_batch)
+        .respond_with(|request: &wiremock::Request| {
+            let calls: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            let responses: Vec<Value> = calls.iter()
+                .map(|call| json!({ "jsonrpc": "2.0", "id": call["id"], "result": ["0x2a", "0x0"] }))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .mount(&server)
+        .await;
+    mock_rpc_call(
+        &server,
+        json!({ "calldata": ["0x1"] }),
+        json!(["0xabc", "0x4164616c6961205072696d65", "0x1", "0x49726f6e", "0x5dc"]),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "params": [{ "calldata": ["0x2"] }] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": 40, "message": "Contract error" },
+        })))
+        .mount(&server)
+        .await;
+
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .asteroid_contract("0x99")
+        .build()
+        .unwrap();
+    let request: BatchQueryRequest =
+        serde_json::from_value(json!({ "account": ACCOUNT, "tokens": ["eth"], "asteroids": [1, 2] })).unwrap();
+    assert!(!request.include_metadata);
+    let result = client.batch_query(request).await.unwrap();
+
+    match &result.balances[..] {
+        [BalanceEntry::Ok { token, balance, metadata: None }] => {
+            assert_eq!(token, "eth");
+            assert_eq!(balance.to_u128(), Some(42));
+        }
+        other => panic!("expected one eth balance, got {:?}", other),
+    }
+    assert_eq!(result.asteroids.len(), 2);
+    match &result.asteroids[0] {
+        AsteroidEntry::Ok(info) => {
+            assert_eq!(info.name, "Adalia Prime");
+            assert_eq!(info.resource_abundances["Iron"], 0.15);
+        }
+        other => panic!("expected asteroid 1 to decode, got {:?}", other),
+    }
+    assert!(matches!(result.asteroids[1], AsteroidEntry::Error { id: 2, .. }));
+    assert_eq!(serde_json::to_value(&result.asteroids[1]).unwrap()["status"], "error");
+}
+
+#[tokio::test]
+async fn throughput_estimate_tracks_rate_limit_and_availability() {
+    let server = MockServer::start().await;
+    let builder = || StarknetClient::builder().rpc_url(&server.uri()).requests_per_second(7);
+
+    let limited = builder().rate_limit_local(true).build().unwrap();
+    assert_eq!(limited.total_rate_limit(), 7);
+    assert_eq!(limited.effective_throughput_estimate(), 7.0);
+
+    // Unlimited local providers count at the configured rate until timed, then
+    // at one request per average response time: finite either way.
+    let local = builder().build().unwrap();
+    assert_eq!(local.effective_throughput_estimate(), 7.0);
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    local.get_network_status().await.unwrap();
+    let measured = local.effective_throughput_estimate();
+    assert!(measured.is_finite() && measured > 0.0, "{}", measured);
+
+    limited.close().await;
+    assert_eq!(limited.effective_throughput_estimate(), 0.0);
+}
+
+#[tokio::test]
+async fn throughput_sums_providers_and_drops_open_breakers() {
+    let parked = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "60"))
+        .mount(&parked)
+        .await;
+    let healthy = MockServer::start().await;
+    mock_rpc(&healthy, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    let client = StarknetClient::builder()
+        .rpc_url(&parked.uri())
+        .rpc_url(&healthy.uri())
+        .requests_per_second(3)
+        .rate_limit_local(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.total_rate_limit(), 6);
+    assert_eq!(client.effective_throughput_estimate(), 6.0);
+
+    // Round-robin reaches the parked provider within two requests.
+    for _ in 0..2 {
+        client.get_network_status().await.unwrap();
+    }
+    assert_eq!(client.total_rate_limit(), 6);
+    assert_eq!(client.effective_throughput_estimate(), 3.0);
+}
+
+#[tokio::test]
+async fn gas_price_history_walks_back_from_head() {
+    let server = MockServer::start().await;
+    for (number, price) in [(8u64, "0x8"), (9, "0x9")] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "params": [{ "block_number": number }] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": latest_block(number, price),
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+    }
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(10, "0xa")).await;
+
+    let history = client_for(&server).gas_price_history(3).await.unwrap();
+
+    assert_eq!(history, [(8, 8), (9, 9), (10, 10)]);
+    assert!(client_for(&server).gas_price_history(0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn metrics_separate_rate_limit_wait_from_rpc_time() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_getBlockWithTxHashes", latest_block(1, "0x1")).await;
+    let client = StarknetClient::builder()
+        .rpc_url(&server.uri())
+        .requests_per_second(2)
+        .rate_limit_local(true)
+        .build()
+        .unwrap();
+
+    // Burst of 2, then each request waits ~500ms for a permit.
+    for _ in 0..3 {
+        client.get_network_status().await.unwrap();
+    }
+
+    let provider = &client.metrics_snapshot().providers[0];
+    assert!(provider.rate_limit_wait_us >= 400_000, "waited {}us", provider.rate_limit_wait_us);
+    assert!(provider.rpc_time_us > 0);
+}
+
+#[tokio::test]
+async fn monotonic_reads_never_go_backwards() {
+    let ahead = MockServer::start().await;
+    let behind = MockServer::start().await;
+    mock_rpc(&ahead, "starknet_getBlockWithTxHashes", latest_block(100, "0x1")).await;
+    mock_rpc(&behind, "starknet_getBlockWithTxHashes", latest_block(99, "0x1")).await;
+    let client = |monotonic: bool| {
+        StarknetClient::builder()
+            .rpc_url(&ahead.uri())
+            .rpc_url(&behind.uri())
+            .monotonic_reads(monotonic)
+            .build()
+            .unwrap()
+    };
+
+    // Round-robin: the second read goes to the provider one block behind.
+    let plain = client(false);
+    assert_eq!(plain.get_network_status().await.unwrap().block_number, 100);
+    assert_eq!(plain.get_network_status().await.unwrap().block_number, 99);
+
+    let monotonic = client(true);
+    assert_eq!(monotonic.get_network_status().await.unwrap().block_number, 100);
+    assert_eq!(monotonic.get_network_status().await.unwrap().block_number, 100);
+}
+
+#[tokio::test]
+async fn allowance_decodes_full_uint256() {
+    use stark_pyrust_chain::types::U256;
+
+    let server = MockServer::start().await;
+    mock_rpc_call(
+        &server,
+        json!({ "calldata": ["0x123", "0x456"] }),
+        json!(["0xffffffffffffffffffffffffffffffff", "0xffffffffffffffffffffffffffffffff"]),
+    )
+    .await;
+
+    let client = client_for(&server);
+
+    assert_eq!(
+        client.get_allowance("0x5", "0x123", "0x456").await.unwrap(),
+        U256::new(u128::MAX, u128::MAX)
+    );
+    assert!(client.get_allowance("0x5", "0x123", "not-hex").await.is_err());
+}
+
+#[tokio::test]
+async fn undecodable_response_carries_raw_felts() {
+    use starknet::core::types::FieldElement;
+
+    let server = MockServer::start().await;
+    mock_rpc(&server, "starknet_call", json!(["0x5"])).await;
+    let client = client_for(&server);
+
+    let err = client.get_allowance("0x5", "0x1", "0x2").await.unwrap_err();
+    match err.downcast_ref::<ClientError>() {
+        Some(ClientError::UnexpectedResponse { entry_point, raw, .. }) => {
+            assert_eq!(entry_point, "allowance");
+            assert_eq!(raw, &[FieldElement::from(5u32)]);
+        }
+        other => panic!("expected UnexpectedResponse, got {:?}", other),
+    }
+    assert!(err.to_string().contains("[0x5]"));
+
+    let raw = client.get_token_balance_raw("0x5", ACCOUNT, None).await.unwrap();
+    assert_eq!(raw, [FieldElement::from(5u32)]);
+}
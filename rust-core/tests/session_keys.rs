@@ -0,0 +1,66 @@
+//! Session key signing and authorization payloads.
+
+use stark_pyrust_chain::session_keys::{MockClock, SessionKey};
+use starknet::core::types::FieldElement;
+
+#[test]
+fn signature_round_trip_and_tamper_rejection() {
+    let key = SessionKey::generate().unwrap();
+    let hash = FieldElement::from_hex_be("0x1234abcd").unwrap();
+
+    let signature = key.sign(hash).unwrap();
+
+    assert!(SessionKey::verify(&key.public_key, hash, &signature).unwrap());
+    let tampered = hash + FieldElement::ONE;
+    assert!(!SessionKey::verify(&key.public_key, tampered, &signature).unwrap());
+}
+
+#[test]
+fn expiry_lifecycle_with_mock_clock() {
+    let clock = MockClock::new(1_700_000_000);
+    let key = SessionKey::generate_with_ttl_on(&clock, 3600).unwrap();
+    assert_eq!(key.expires_at, 1_700_003_600);
+
+    clock.advance(3599);
+    assert!(!key.is_expired_on(&clock));
+    clock.advance(1);
+    assert!(key.is_expired_on(&clock));
+
+    let forever = SessionKey::generate().unwrap();
+    clock.set(u64::MAX);
+    assert!(!forever.is_expired_on(&clock));
+}
+
+#[test]
+fn seeded_rng_generates_reproducible_keys() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let a = SessionKey::generate_with_rng(&mut StdRng::seed_from_u64(7)).unwrap();
+    let b = SessionKey::generate_with_rng(&mut StdRng::seed_from_u64(7)).unwrap();
+    let c = SessionKey::generate_with_rng(&mut StdRng::seed_from_u64(8)).unwrap();
+
+    assert_eq!(a.private_key, b.private_key);
+    assert_eq!(a.public_key, b.public_key);
+    assert_ne!(a.private_key, c.private_key);
+
+    let hash = FieldElement::from_hex_be("0x42").unwrap();
+    assert!(SessionKey::verify(&a.public_key, hash, &a.sign(hash).unwrap()).unwrap());
+}
+
+#[test]
+fn derived_keys_are_deterministic_per_index() {
+    let master = "0x1234567890abcdef";
+
+    let first = SessionKey::derive(master, 7, 1_700_000_000).unwrap();
+    let again = SessionKey::derive(master, 7, 1_700_000_000).unwrap();
+    assert_eq!(first.private_key, again.private_key);
+    assert_eq!(first.public_key, again.public_key);
+    assert_eq!(first.expires_at, 1_700_000_000);
+
+    let other = SessionKey::derive(master, 8, 1_700_000_000).unwrap();
+    assert_ne!(first.private_key, other.private_key);
+
+    assert!(SessionKey::derive("0x0", 0, 0).is_err());
+    assert!(SessionKey::derive("not hex", 0, 0).is_err());
+}
@@ -0,0 +1,22 @@
+//! Starknet ID domain encoding.
+
+use stark_pyrust_chain::starknet_id::{decode_domain, encode_domain};
+use starknet::core::types::FieldElement;
+
+#[test]
+fn domains_encode_to_known_felts_and_round_trip() {
+    let felt = |n: u64| FieldElement::from(n);
+
+    assert_eq!(encode_domain("ben").unwrap(), [felt(18925)]);
+    assert_eq!(encode_domain("fricoben.stark").unwrap(), [felt(1_499_554_868_251)]);
+    assert_eq!(decode_domain(&[felt(1_499_554_868_251)]).unwrap(), "fricoben.stark");
+
+    // A trailing `a` uses the escape digit so it survives decoding.
+    let encoded = encode_domain("sub.banana.stark").unwrap();
+    assert_eq!(encoded.len(), 2);
+    assert_eq!(decode_domain(&encoded).unwrap(), "sub.banana.stark");
+
+    assert!(encode_domain("bad_name.stark").is_err());
+    assert!(encode_domain(".stark").is_err());
+    assert!(decode_domain(&[]).is_err());
+}
@@ -0,0 +1,479 @@
+//! Planner tests over small hand-built recipe graphs and the shared fixtures.
+
+use stark_pyrust_chain::fixtures::{self, qty, recipe, sample_graph, COMPONENT, METAL, ORE, WIDGET};
+use stark_pyrust_chain::supply_chain::{
+    FacilityFeasibility, GraphLint, Metric, PathOrder, PlanSummary, ProductionFeasibility, ProductionPlan, Recipe, SupplyChainGraph,
+    PLAN_SCHEMA_VERSION,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn net_flow_nets_out_catalysts() {
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe(
+        "Catalytic Crack",
+        recipe(&[("Crude", 10), ("Catalyst", 1)], &[("Fuel", 6), ("Catalyst", 1)], 30),
+    );
+
+    let flow = graph.net_flow("Catalytic Crack").unwrap();
+
+    assert_eq!(flow["Crude"], -10);
+    assert_eq!(flow["Fuel"], 6);
+    assert_eq!(flow["Catalyst"], 0);
+    assert!(graph.net_flow("Missing").is_none());
+}
+
+/// Default graph (250 Iron Ore + 20 Fuel -> 100 Steel) plus 2 Steel -> 1 Beam.
+fn beam_graph() -> SupplyChainGraph {
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe("Forge Beam", recipe(&[("Steel", 2)], &[("Beam", 1)], 60));
+    graph
+}
+
+#[test]
+fn raw_material_cost_rounds_up_whole_batches() {
+    let raw = beam_graph().raw_material_cost("Beam", 60).unwrap();
+
+    // 120 Steel -> 2 refining runs.
+    assert_eq!(raw["Iron Ore"], 500);
+    assert_eq!(raw["Fuel"], 40);
+    assert!(!raw.contains_key("Steel"));
+}
+
+#[test]
+fn can_produce_reports_raw_shortfall_after_using_stock() {
+    let graph = beam_graph();
+    // 20 Steel on hand covers 10 Beams; the other 40 Beams need 80 Steel = 1 run.
+    let inventory = qty(&[("Steel", 20), ("Iron Ore", 100), ("Fuel", 20)]);
+
+    let result = graph.can_produce("Beam", 50, &inventory);
+
+    assert_eq!(result, ProductionFeasibility::Shortfall(qty(&[("Iron Ore", 150)])));
+
+    let plenty = qty(&[("Steel", 100)]);
+    assert_eq!(graph.can_produce("Beam", 50, &plenty), ProductionFeasibility::Feasible);
+}
+
+#[test]
+fn all_resources_includes_inputs_and_outputs_sorted() {
+    let resources: Vec<String> = beam_graph().all_resources().into_iter().collect();
+
+    assert_eq!(resources, ["Beam", "Fuel", "Iron Ore", "Steel"]);
+}
+
+#[test]
+fn recipe_margin_needs_every_price() {
+    let mut graph = SupplyChainGraph::new();
+    graph.set_resource_price("Iron Ore", 1.0);
+    graph.set_resource_price("Steel", 4.0);
+    assert_eq!(graph.recipe_margin("Refine Steel"), None);
+
+    graph.set_resource_price("Fuel", 5.0);
+    // 100 * 4 - (250 * 1 + 20 * 5)
+    assert_eq!(graph.recipe_margin("Refine Steel"), Some(50.0));
+}
+
+#[test]
+fn existence_predicates() {
+    let graph = beam_graph();
+
+    assert!(graph.has_recipe("Forge Beam"));
+    assert!(!graph.has_recipe("Beam"));
+    assert!(graph.produces("Beam"));
+    assert!(!graph.produces("Iron Ore"));
+}
+
+#[test]
+fn production_plan_round_trips_through_json() {
+    let plan = beam_graph().production_plan("Beam", 50).unwrap();
+
+    assert_eq!(plan.schema_version, PLAN_SCHEMA_VERSION);
+    assert_eq!(plan.root.recipe.as_deref(), Some("Forge Beam"));
+    assert_eq!(plan.root.runs, 50);
+    assert_eq!(plan.root.inputs[0].resource, "Steel");
+    assert_eq!(plan.root.inputs[0].runs, 1);
+    let raw: HashMap<String, u32> = plan.raw_materials.clone().into_iter().collect();
+    assert_eq!(raw, beam_graph().raw_material_cost("Beam", 50).unwrap());
+
+    let json = plan.to_json().unwrap();
+    assert_eq!(ProductionPlan::from_json(&json).unwrap(), plan);
+}
+
+#[test]
+fn production_plan_rejects_other_schema_versions() {
+    let mut value: serde_json::Value =
+        serde_json::from_str(&beam_graph().production_plan("Beam", 1).unwrap().to_json().unwrap()).unwrap();
+    value["schema_version"] = serde_json::json!(PLAN_SCHEMA_VERSION + 1);
+
+    assert!(ProductionPlan::from_json(&value.to_string()).is_err());
+}
+
+#[test]
+fn feasibility_serializes_with_status_tag() {
+    let json = serde_json::to_value(ProductionFeasibility::Shortfall(qty(&[("Fuel", 3)]))).unwrap();
+
+    assert_eq!(json, serde_json::json!({ "status": "shortfall", "missing": { "Fuel": 3 } }));
+}
+
+#[test]
+fn sample_graph_costs_out_the_whole_chain() {
+    let graph = sample_graph();
+
+    assert_eq!(graph.len(), 3);
+    assert_eq!(graph.production_depth(WIDGET), Some(3));
+    // 5 Widgets = 10 Components = 30 Metal = 15 smelts = 60 Ore.
+    assert_eq!(graph.raw_material_cost(WIDGET, 5).unwrap(), qty(&[(ORE, 60)]));
+    assert_eq!(
+        graph.bill_of_materials(WIDGET, 5).unwrap(),
+        qty(&[(WIDGET, 5), (COMPONENT, 10), (METAL, 30), (ORE, 60)])
+    );
+    assert_eq!(graph.lint(), []);
+}
+
+#[test]
+fn sample_graph_unit_cost_is_priced_in_ore() {
+    // One Widget = 12 Ore at 1.0.
+    let (total, parts) = sample_graph().unit_cost_breakdown(WIDGET, &fixtures::sample_prices()).unwrap();
+
+    assert!((total - 12.0).abs() < 1e-9);
+    assert_eq!(parts.len(), 1);
+    assert!((parts[ORE] - 12.0).abs() < 1e-9);
+}
+
+#[test]
+fn sample_graph_shares_factories_between_tiers() {
+    // 30 Widgets/h: assembly needs 1 Factory and machining 60 Components/h 1 more;
+    // those take 180 Metal/h, i.e. 90 smelts x 30s = 0.75 Refineries.
+    let have = |factories: u32, refineries: u32| {
+        HashMap::from([("Factory".to_string(), factories), ("Refinery".to_string(), refineries)])
+    };
+
+    assert_eq!(sample_graph().fits_within(WIDGET, 30.0, &have(2, 1)), FacilityFeasibility::Feasible);
+    assert_eq!(
+        sample_graph().fits_within(WIDGET, 30.0, &have(1, 1)),
+        FacilityFeasibility::Shortfall(HashMap::from([("Factory".to_string(), 1)]))
+    );
+    assert_eq!(
+        sample_graph().fits_within(WIDGET, 30.0, &have(2, 0)),
+        FacilityFeasibility::Shortfall(HashMap::from([("Refinery".to_string(), 1)]))
+    );
+}
+
+#[test]
+fn production_depth_counts_longest_chain() {
+    let mut graph = beam_graph();
+    // Beam (2) sits on Steel (1); a Truss needing both is one level above the deeper.
+    graph.add_recipe("Weld Truss", recipe(&[("Beam", 4), ("Steel", 10)], &[("Truss", 1)], 60));
+
+    assert_eq!(graph.production_depth("Iron Ore"), Some(0));
+    assert_eq!(graph.production_depth("Steel"), Some(1));
+    assert_eq!(graph.production_depth("Beam"), Some(2));
+    assert_eq!(graph.production_depth("Truss"), Some(3));
+}
+
+#[test]
+fn production_path_lists_fastest_then_cheapest() {
+    let mut graph = SupplyChainGraph::new(); // "Refine Steel": 10s
+    graph.add_recipe("Arc Furnace", recipe(&[("Scrap", 120)], &[("Steel", 100)], 4));
+    graph.set_resource_price("Iron Ore", 1.0);
+    graph.set_resource_price("Fuel", 1.0);
+    graph.set_resource_price("Scrap", 5.0);
+
+    assert_eq!(graph.find_production_path("Steel").unwrap(), ["Arc Furnace", "Refine Steel"]);
+    assert_eq!(
+        graph.find_production_path_by("Steel", PathOrder::Cheapest).unwrap(),
+        ["Refine Steel", "Arc Furnace"]
+    );
+}
+
+#[test]
+fn cloned_graph_is_independent() {
+    let original = beam_graph();
+    let mut branch = original.clone();
+
+    branch.remove_recipe("Refine Steel").unwrap();
+    branch.add_recipe("Forge Beam", recipe(&[("Iron Ore", 1)], &[("Beam", 1)], 1));
+
+    assert!(!branch.produces("Steel"));
+    assert_eq!(branch.find_production_path("Beam").unwrap(), ["Forge Beam"]);
+    assert_eq!(branch.raw_material_cost("Beam", 5).unwrap(), qty(&[("Iron Ore", 5)]));
+
+    assert!(original.produces("Steel"));
+    assert_eq!(original.recipe("Forge Beam").unwrap().inputs, qty(&[("Steel", 2)]));
+}
+
+#[test]
+fn bottleneck_is_the_step_needing_most_facilities() {
+    // 100 Beam/h = 100 Forge runs (60s each) -> 1.67 forges;
+    // 200 Steel/h = 2 Refine runs (10s each) -> 0.006 refineries.
+    let graph = beam_graph();
+
+    let facilities = graph.facilities_required("Beam", 100.0).unwrap();
+    assert!((facilities["Forge Beam"] - 100.0 / 60.0).abs() < 1e-9);
+    assert!((facilities["Refine Steel"] - 2.0 * 10.0 / 3600.0).abs() < 1e-9);
+
+    assert_eq!(graph.bottleneck("Beam", 100.0).as_deref(), Some("Forge Beam"));
+    assert_eq!(graph.bottleneck("Iron Ore", 100.0), None);
+}
+
+#[test]
+fn lint_flags_typos_and_empty_recipes() {
+    let mut graph = beam_graph();
+    assert!(graph.lint().is_empty());
+
+    graph.add_recipe("Smelt", recipe(&[("IronOre", 10)], &[("Steel", 1)], 5));
+    graph.add_recipe("Void", recipe(&[("Steel", 1)], &[], 5));
+
+    assert_eq!(
+        graph.lint(),
+        [
+            GraphLint::EmptyOutputs { recipe: "Void".to_string() },
+            GraphLint::UnknownInput { resource: "IronOre".to_string(), used_by: vec!["Smelt".to_string()] },
+            GraphLint::NearDuplicateNames { names: vec!["Iron Ore".to_string(), "IronOre".to_string()] },
+        ]
+    );
+}
+
+#[test]
+fn preferred_recipe_wins_ties() {
+    let mut graph = SupplyChainGraph::new(); // "Refine Steel": 10s, 250 Iron Ore + 20 Fuel
+    graph.add_recipe("Scrap Steel", Recipe { preferred: true, ..recipe(&[("Scrap", 270)], &[("Steel", 100)], 10) });
+    for resource in ["Iron Ore", "Fuel", "Scrap"] {
+        graph.set_resource_price(resource, 1.0);
+    }
+
+    assert_eq!(graph.find_production_path("Steel").unwrap(), ["Scrap Steel", "Refine Steel"]);
+    assert_eq!(
+        graph.find_production_path_by("Steel", PathOrder::Cheapest).unwrap(),
+        ["Scrap Steel", "Refine Steel"]
+    );
+    let plan = graph.production_plan("Steel", 100).unwrap();
+    assert_eq!(plan.root.recipe.as_deref(), Some("Scrap Steel"));
+    assert_eq!(graph.raw_material_cost("Steel", 100).unwrap(), qty(&[("Scrap", 270)]));
+}
+
+#[test]
+fn graph_collects_from_recipe_iterator() {
+    let graph: SupplyChainGraph = [
+        ("Smelt".to_string(), recipe(&[("Ore", 2)], &[("Ingot", 1)], 5)),
+        ("Press".to_string(), recipe(&[("Ingot", 3)], &[("Plate", 1)], 5)),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(graph.len(), 2);
+    assert!(!graph.has_recipe("Refine Steel"));
+    assert_eq!(graph.find_production_path("Plate").unwrap(), ["Press"]);
+    assert_eq!(graph.raw_material_cost("Plate", 2).unwrap(), qty(&[("Ore", 12)]));
+
+    assert!(SupplyChainGraph::default().has_recipe("Refine Steel"));
+}
+
+#[test]
+fn per_unit_inputs_normalizes_by_output_quantity() {
+    let mut graph = SupplyChainGraph::new(); // 250 Iron Ore + 20 Fuel -> 100 Steel
+    graph.add_recipe("Nothing", recipe(&[("Fuel", 1)], &[("Slag", 0)], 1));
+
+    let per_steel = graph.per_unit_inputs("Refine Steel", "Steel").unwrap();
+    assert_eq!(per_steel["Iron Ore"], 2.5);
+    assert_eq!(per_steel["Fuel"], 0.2);
+
+    assert!(graph.per_unit_inputs("Refine Steel", "Beam").is_none());
+    assert!(graph.per_unit_inputs("Nothing", "Slag").is_none());
+    assert!(graph.per_unit_inputs("Missing", "Steel").is_none());
+}
+
+#[test]
+fn plan_summary_bundles_costs_and_timing() {
+    let graph = beam_graph();
+    let steel_time = graph.recipe("Refine Steel").unwrap().process_time_seconds as u64;
+
+    // 60 Beam = 60 runs of 60s, after 2 Steel runs.
+    let summary = graph.plan_summary("Beam", 60).unwrap();
+
+    assert_eq!(summary.raw_materials.get("Iron Ore"), Some(&500));
+    assert_eq!(summary.bill_of_materials.get("Steel"), Some(&120));
+    assert_eq!(summary.facilities, 2);
+    assert_eq!(summary.critical_path_seconds, 60 * 60 + 2 * steel_time);
+    assert_eq!(summary.bottleneck.as_deref(), Some("Forge Beam"));
+
+    let json = serde_json::to_string(&summary).unwrap();
+    assert_eq!(serde_json::from_str::<PlanSummary>(&json).unwrap(), summary);
+    assert_eq!(graph.plan_summary("Iron Ore", 5).unwrap().bottleneck, None);
+}
+
+#[test]
+fn bill_of_materials_lists_every_tier() {
+    let graph = beam_graph();
+
+    let bom = graph.bill_of_materials("Beam", 60).unwrap();
+    let raw = graph.raw_material_cost("Beam", 60).unwrap();
+
+    assert_eq!(bom, qty(&[("Beam", 60), ("Steel", 120), ("Iron Ore", 500), ("Fuel", 40)]));
+    assert_eq!(raw, qty(&[("Iron Ore", 500), ("Fuel", 40)]));
+}
+
+#[test]
+fn bill_of_materials_credits_byproducts() {
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe("Gasify", recipe(&[("Coal", 5)], &[("Gas", 2)], 30));
+    graph.add_recipe("Crack", recipe(&[("Crude", 10)], &[("Fuel", 6), ("Gas", 2)], 30));
+    graph.add_recipe("Blend", recipe(&[("Fuel", 3), ("Gas", 2)], &[("Mix", 1)], 30));
+
+    // Cracking for Fuel leaves 2 Gas over, so Gasify never runs.
+    let bom = graph.bill_of_materials("Mix", 1).unwrap();
+
+    assert_eq!(bom, qty(&[("Mix", 1), ("Fuel", 3), ("Gas", 2), ("Crude", 10)]));
+    assert_eq!(graph.raw_material_cost("Mix", 1).unwrap(), qty(&[("Crude", 10), ("Coal", 5)]));
+}
+
+#[test]
+fn output_rate_is_units_per_second() {
+    let mut graph = SupplyChainGraph::new(); // 250 Iron Ore + 20 Fuel -> 100 Steel
+    graph.add_recipe("Instant", recipe(&[("Fuel", 1)], &[("Heat", 5)], 0));
+
+    let steel_time = graph.recipe("Refine Steel").unwrap().process_time_seconds as f64;
+    assert_eq!(graph.output_rate("Refine Steel", "Steel"), Some(100.0 / steel_time));
+    assert_eq!(graph.output_rate("Instant", "Heat"), Some(f64::INFINITY));
+    assert!(graph.output_rate("Refine Steel", "Beam").is_none());
+    assert!(graph.output_rate("Missing", "Steel").is_none());
+}
+
+#[test]
+fn best_recipes_for_reorders_by_metric() {
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe("Quick", recipe(&[("Ore", 10)], &[("Plate", 2)], 10));
+    graph.add_recipe("Bulk", recipe(&[("Ore", 30)], &[("Plate", 10)], 100));
+    graph.add_recipe("Scrap", recipe(&[("Scrap", 4)], &[("Plate", 1)], 20));
+    graph.set_resource_price("Ore", 1.0);
+    graph.set_resource_price("Scrap", 0.5);
+
+    let names = |metric| graph.best_recipes_for("Plate", metric).into_iter().map(|(n, _)| n).collect::<Vec<_>>();
+    assert_eq!(names(Metric::Time), ["Quick", "Bulk", "Scrap"]); // 5s, 10s, 20s per plate
+    assert_eq!(names(Metric::Cost), ["Scrap", "Bulk", "Quick"]); // 2, 3, 5 per plate
+    assert_eq!(names(Metric::Yield), ["Bulk", "Quick", "Scrap"]); // 10, 2, 1 per run
+
+    assert_eq!(graph.best_recipes_for("Plate", Metric::Cost)[0].1, 2.0);
+    assert!(graph.best_recipes_for("Ore", Metric::Time).is_empty());
+}
+
+#[test]
+fn yield_over_counts_completed_runs_only() {
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe("Smelt", recipe(&[("Iron Ore", 5)], &[("Steel", 2)], 600));
+
+    // 8h = 48 runs of 10 min per facility; the half-finished 49th doesn't count.
+    let produced = graph.yield_over("Smelt", 3, Duration::from_secs(8 * 3600 + 300));
+
+    assert_eq!(produced["Steel"], 3 * 48 * 2);
+    assert_eq!(produced["Iron Ore"], 3 * 48 * 5);
+    assert_eq!(graph.yield_over("Smelt", 3, Duration::from_secs(599))["Steel"], 0);
+    assert!(graph.yield_over("Missing", 1, Duration::from_secs(3600)).is_empty());
+}
+
+#[test]
+fn csv_round_trip_is_stable() {
+    let mut graph = beam_graph();
+    graph.add_recipe("Weld, Truss", recipe(&[("Beam", 4)], &[("Truss \"A\"", 1)], 90));
+
+    let csv = graph.to_csv();
+    assert_eq!(
+        csv.lines().take(4).collect::<Vec<_>>(),
+        [
+            "recipe,role,resource,quantity,process_time",
+            "Forge Beam,input,Steel,2,60",
+            "Forge Beam,output,Beam,1,60",
+            "Refine Steel,input,Fuel,20,10",
+        ]
+    );
+    assert!(csv.contains("\"Weld, Truss\",output,\"Truss \"\"A\"\"\",1,90"));
+
+    let restored = SupplyChainGraph::from_csv(&csv).unwrap();
+    assert_eq!(restored.len(), 3);
+    assert_eq!(restored.to_csv(), csv);
+    assert_eq!(restored.raw_material_cost("Beam", 60).unwrap(), graph.raw_material_cost("Beam", 60).unwrap());
+
+    // Line breaks inside quoted names survive, and later rows keep their line numbers.
+    graph.add_recipe("Cast\nIngot", recipe(&[("Ore", 2)], &[("Ingot\r\nBar", 1)], 15));
+    let csv = graph.to_csv();
+    let restored = SupplyChainGraph::from_csv(&csv).unwrap();
+    assert_eq!(restored.len(), 4);
+    assert_eq!(restored.to_csv(), csv);
+    let err = SupplyChainGraph::from_csv("\"Cast\nIngot\",input,Ore,2,15\nSmelt,input,Ore,x,5").unwrap_err();
+    assert!(err.to_string().starts_with("Line 3:"), "{err}");
+
+    assert!(SupplyChainGraph::from_csv("Smelt,catalyst,Ore,1,5").is_err());
+    assert!(SupplyChainGraph::from_csv("Smelt,input,Ore,1,5\nSmelt,output,Ingot,1,6").is_err());
+}
+
+#[test]
+fn price_change_reaches_recipes_through_intermediates() {
+    let mut graph = beam_graph();
+    graph.add_recipe("Weld Frame", recipe(&[("Beam", 4), ("Rivet", 8)], &[("Frame", 1)], 90));
+    graph.add_recipe("Press Rivet", recipe(&[("Copper", 1)], &[("Rivet", 10)], 5));
+
+    let affected = graph.recipes_affected_by("Iron Ore");
+    let mut names: Vec<&str> = affected.iter().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["Forge Beam", "Refine Steel", "Weld Frame"]);
+
+    assert_eq!(graph.recipes_affected_by("Copper").len(), 2);
+    assert!(graph.recipes_affected_by("Frame").is_empty());
+}
+
+#[test]
+fn fits_within_pools_shared_facility_types_and_reports_shortfall() {
+    let factory = |r: Recipe| Recipe { facility: Some("Factory".to_string()), ..r };
+    let mut graph = SupplyChainGraph::new();
+    graph.add_recipe("Forge Beam", factory(recipe(&[("Steel", 2)], &[("Beam", 1)], 60)));
+    graph.add_recipe("Weld Frame", factory(recipe(&[("Beam", 2)], &[("Frame", 1)], 36)));
+    let base = |factories: u32| HashMap::from([("Factory".to_string(), factories), ("Refinery".to_string(), 1)]);
+
+    // 45 Frames/h: welding needs 0.45 Factories and forging 1.5, so 2 in total.
+    assert_eq!(
+        graph.fits_within("Frame", 45.0, &base(1)),
+        FacilityFeasibility::Shortfall(HashMap::from([("Factory".to_string(), 1)]))
+    );
+    assert_eq!(graph.fits_within("Frame", 45.0, &base(2)), FacilityFeasibility::Feasible);
+    assert_eq!(
+        graph.fits_within("Frame", 45.0, &HashMap::new()),
+        FacilityFeasibility::Shortfall(HashMap::from([("Factory".to_string(), 2), ("Refinery".to_string(), 1)]))
+    );
+
+    graph.add_recipe("Melt Frame", Recipe { preferred: true, ..recipe(&[("Frame", 1)], &[("Steel", 2)], 5) });
+    assert!(matches!(graph.fits_within("Frame", 1.0, &base(9)), FacilityFeasibility::Unplannable(_)));
+}
+
+#[test]
+fn fingerprint_ignores_insertion_order_but_tracks_recipe_changes() {
+    let recipes = [
+        ("Forge Beam", recipe(&[("Steel", 2)], &[("Beam", 1)], 60)),
+        ("Press Rivet", recipe(&[("Copper", 1)], &[("Rivet", 10)], 5)),
+        ("Weld Frame", recipe(&[("Beam", 4), ("Rivet", 8)], &[("Frame", 1)], 90)),
+    ];
+    let forward: SupplyChainGraph = recipes.iter().cloned().map(|(n, r)| (n.to_string(), r)).collect();
+    let mut reversed: SupplyChainGraph = recipes.iter().rev().cloned().map(|(n, r)| (n.to_string(), r)).collect();
+    reversed.set_version("patch-7");
+
+    assert_eq!(forward.fingerprint(), reversed.fingerprint());
+    assert_eq!(reversed.version(), Some("patch-7"));
+    assert_eq!(forward.version(), None);
+
+    reversed.add_recipe("Press Rivet", recipe(&[("Copper", 1)], &[("Rivet", 12)], 5));
+    assert_ne!(forward.fingerprint(), reversed.fingerprint());
+}
+
+#[test]
+fn unit_cost_breakdown_attributes_raw_costs_and_names_unpriced_inputs() {
+    let prices = HashMap::from([("Iron Ore".to_string(), 2.0), ("Fuel".to_string(), 5.0)]);
+
+    // One Beam = 2 Steel = 5 Iron Ore + 0.4 Fuel.
+    let (total, parts) = beam_graph().unit_cost_breakdown("Beam", &prices).unwrap();
+    assert!((parts["Iron Ore"] - 10.0).abs() < 1e-9);
+    assert!((parts["Fuel"] - 2.0).abs() < 1e-9);
+    assert!((total - 12.0).abs() < 1e-9);
+
+    let err = beam_graph().unit_cost_breakdown("Beam", &HashMap::from([("Iron Ore".to_string(), 2.0)])).unwrap_err();
+    assert!(err.to_string().contains("Fuel"));
+}
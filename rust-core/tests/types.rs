@@ -0,0 +1,241 @@
+//! Serialization and formatting of the public data types.
+
+use starknet::core::types::FieldElement;
+use stark_pyrust_chain::types::{
+    felt_to_address_string, is_valid_felt, Calldata, normalize_address, suggested_gas_price, Address, FeeEstimate, FeeUnit,
+    IntoAddress, NetworkStatus, U256,
+};
+use stark_pyrust_chain::units::{format_balance, format_balance_rounded, RoundingMode};
+
+#[test]
+fn u256_decimal_round_trip() {
+    let max = U256::new(u128::MAX, u128::MAX);
+    let text = max.to_string();
+
+    assert_eq!(
+        text,
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+    assert_eq!(text.parse::<U256>().unwrap(), max);
+    assert_eq!(U256::new(0, 1).to_string(), "340282366920938463463374607431768211456");
+    assert!("115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        .parse::<U256>()
+        .is_err());
+}
+
+#[test]
+fn u256_serializes_as_string() {
+    let json = serde_json::to_string(&U256::from(1000)).unwrap();
+
+    assert_eq!(json, "\"1000\"");
+    assert_eq!(serde_json::from_str::<U256>(&json).unwrap(), U256::from(1000));
+}
+
+#[test]
+fn format_balance_is_exact() {
+    assert_eq!(format_balance(1_500_000_000_000_000_000u128, 18), "1.5");
+    assert_eq!(format_balance(1u128, 18), "0.000000000000000001");
+    assert_eq!(format_balance(2_000_000u128, 6), "2");
+    assert_eq!(format_balance(42u128, 0), "42");
+    assert_eq!(format_balance(U256::new(0, 1), 18), "340282366920938463463.374607431768211456");
+}
+
+#[test]
+fn format_balance_rounded_truncates_or_rounds_half_up() {
+    use RoundingMode::{HalfUp, Truncate};
+    let sway = 1_234_567_800_000_000_000_000u128; // 1234.5678 at 18 decimals
+
+    assert_eq!(format_balance_rounded(sway, 18, 2, Truncate), "1234.56");
+    assert_eq!(format_balance_rounded(sway, 18, 2, HalfUp), "1234.57");
+    // Exactly half rounds up; just under doesn't.
+    assert_eq!(format_balance_rounded(1_250u128, 3, 1, HalfUp), "1.3");
+    assert_eq!(format_balance_rounded(1_249u128, 3, 1, HalfUp), "1.2");
+    // Carry ripples into the whole part.
+    assert_eq!(format_balance_rounded(9_995u128, 3, 2, HalfUp), "10.00");
+    assert_eq!(format_balance_rounded(9_995u128, 3, 0, HalfUp), "10");
+    assert_eq!(format_balance_rounded(9_995u128, 3, 0, Truncate), "9");
+    // More places than the token has: zero-padded.
+    assert_eq!(format_balance_rounded(15u128, 1, 3, HalfUp), "1.500");
+}
+
+#[test]
+fn network_and_selection_parse_from_config_strings() {
+    use stark_pyrust_chain::network::Network;
+    use stark_pyrust_chain::starknet_client::Selection;
+
+    assert_eq!("Mainnet".parse::<Network>().unwrap(), Network::Mainnet);
+    assert_eq!("sepolia".parse::<Network>().unwrap(), Network::Sepolia);
+    assert_eq!(
+        "http://localhost:5050".parse::<Network>().unwrap(),
+        Network::Custom("http://localhost:5050".to_string())
+    );
+    assert!("goerli".parse::<Network>().is_err());
+
+    assert_eq!("round-robin".parse::<Selection>().unwrap(), Selection::RoundRobin);
+    assert_eq!("LOWEST_LATENCY".parse::<Selection>().unwrap(), Selection::LowestLatency);
+    assert!("random".parse::<Selection>().is_err());
+}
+
+#[test]
+fn result_types_display_and_round_trip() {
+    let status = NetworkStatus {
+        block_number: 123456,
+        l1_gas_price_wei: 12_000_000_000,
+        l1_gas_price_fri: 0,
+        is_pending: false,
+    };
+    assert_eq!(status.to_string(), "block 123456 @ 12 gwei, pending=false");
+    let json = serde_json::to_string(&status).unwrap();
+    assert_eq!(serde_json::from_str::<NetworkStatus>(&json).unwrap(), status);
+
+    let fee = l1_only_fee(10, 3);
+    assert_eq!(fee.to_string(), "fee 30 fri (l1 gas 10 @ 3)");
+    assert_eq!(serde_json::to_value(&fee).unwrap()["unit"], "fri");
+}
+
+fn l1_only_fee(consumed: u128, price: u128) -> FeeEstimate {
+    FeeEstimate {
+        l1_gas_consumed: consumed,
+        l1_gas_price: price,
+        l2_gas_consumed: 0,
+        l2_gas_price: 0,
+        l1_data_gas_consumed: 0,
+        l1_data_gas_price: 0,
+        overall_fee: consumed * price,
+        unit: FeeUnit::Fri,
+    }
+}
+
+#[test]
+fn fee_estimate_scales_into_bounds() {
+    let fee = l1_only_fee(1000, 7);
+
+    assert_eq!(fee.max_fee(1.1), 7700);
+    let bounds = fee.resource_bounds(1.5);
+    assert_eq!(bounds.l1_gas.max_amount, 1500);
+    assert_eq!(bounds.l1_gas.max_price_per_unit, 11); // 10.5 rounded up
+    assert_eq!(bounds.l2_gas.max_amount, 0);
+}
+
+#[test]
+fn addresses_keep_leading_zeros() {
+    let small = felt_to_address_string(&FieldElement::from(0x123u32));
+    assert_eq!(small.len(), 66);
+    assert_eq!(small, format!("0x{:0>64}", "123"));
+
+    assert_eq!(normalize_address("0x0123").unwrap(), small);
+    assert_eq!(normalize_address("0X00000123").unwrap(), small);
+    assert!(normalize_address("0xnot-hex").is_err());
+}
+
+#[test]
+fn contract_registry_seeds_fee_tokens_and_accepts_overrides() {
+    use stark_pyrust_chain::network::{ContractRegistry, Network};
+
+    let mut registry = ContractRegistry::for_network(&Network::Sepolia);
+    assert!(registry.get(ContractRegistry::ETH).is_some());
+    assert!(registry.get(ContractRegistry::STARKNET_ID).is_some());
+    for unseeded in [ContractRegistry::SWAY, ContractRegistry::INTERACT, ContractRegistry::ASTEROIDS] {
+        assert!(registry.get(unseeded).is_none());
+    }
+    assert!(ContractRegistry::for_network(&Network::Custom("http://localhost:5050".into())).get("eth").is_none());
+
+    registry.register_contract("SWAY", "0x0abc").unwrap();
+    assert_eq!(registry.get("sway").unwrap(), format!("0x{:0>64}", "abc"));
+    assert!(registry.register_contract("bad", "xyz").is_err());
+}
+
+#[test]
+fn gas_price_converts_to_gwei_and_eth() {
+    let status = NetworkStatus {
+        block_number: 1,
+        l1_gas_price_wei: 12_500_000_000,
+        l1_gas_price_fri: 0,
+        is_pending: false,
+    };
+
+    assert_eq!(status.gas_price_gwei(), 12.5);
+    assert_eq!(status.gas_price_eth(), 0.0000000125);
+}
+
+#[test]
+fn felt_validation_stops_at_the_stark_prime() {
+    let prime = "0x800000000000011000000000000000000000000000000000000000000000001";
+    let prime_minus_one = "0x800000000000011000000000000000000000000000000000000000000000000";
+
+    assert!(is_valid_felt(prime_minus_one));
+    assert!(!is_valid_felt(prime));
+    assert!(is_valid_felt("0"));
+    assert!(is_valid_felt(
+        "3618502788666131213697322783095070105623107215331596699973092056135872020480"
+    ));
+    assert!(!is_valid_felt(
+        "3618502788666131213697322783095070105623107215331596699973092056135872020481"
+    ));
+    assert!(!is_valid_felt("0x"));
+    assert!(!is_valid_felt("0xzz"));
+
+    assert!(normalize_address(prime_minus_one).is_ok());
+    let err = normalize_address(prime).unwrap_err();
+    assert!(err.to_string().contains("below the Stark prime"));
+}
+
+#[test]
+fn suggested_gas_price_uses_nearest_rank() {
+    let history: Vec<(u64, u128)> = [50, 10, 40, 20, 30].iter().enumerate().map(|(i, &p)| (i as u64, p)).collect();
+
+    assert_eq!(suggested_gas_price(&history, 50.0), Some(30));
+    assert_eq!(suggested_gas_price(&history, 90.0), Some(50));
+    assert_eq!(suggested_gas_price(&history, 0.0), Some(10));
+    assert_eq!(suggested_gas_price(&history, 150.0), Some(50));
+    assert_eq!(suggested_gas_price(&[], 50.0), None);
+}
+
+#[test]
+fn address_parses_displays_and_serializes_canonically() {
+    let address: Address = "0xABC".parse().unwrap();
+
+    assert_eq!(address.felt(), FieldElement::from(0xabcu32));
+    assert_eq!(address.to_string(), format!("0x{:0>64}", "abc"));
+    assert_eq!(serde_json::to_value(address).unwrap(), serde_json::json!(address.to_string()));
+    assert_eq!(serde_json::from_str::<Address>("\"0xabc\"").unwrap(), address);
+
+    assert!("not-hex".parse::<Address>().is_err());
+    assert!(serde_json::from_str::<Address>("\"0xzz\"").is_err());
+    assert_eq!(FieldElement::from(0xabcu32).into_address().unwrap(), address);
+    assert_eq!(String::from("abc").into_address().unwrap(), address);
+}
+
+#[test]
+fn calldata_encodes_u256_and_length_prefixed_arrays() {
+    let felt = |hex: &str| FieldElement::from_hex_be(hex).unwrap();
+    let mut calldata = Calldata::new();
+    calldata
+        .push_address("0x123")
+        .unwrap()
+        .push_u256(U256::new(5, 1))
+        .push_array(&[felt("0xa"), felt("0xb")])
+        .push_array(&[])
+        .push_felt(felt("0x7"));
+
+    assert!(calldata.push_address("not an address").is_err());
+    assert_eq!(
+        calldata.into_vec(),
+        vec![felt("0x123"), felt("0x5"), felt("0x1"), felt("0x2"), felt("0xa"), felt("0xb"), felt("0x0"), felt("0x7")]
+    );
+}
+
+#[test]
+fn asteroid_info_serializes_resource_abundances() {
+    use stark_pyrust_chain::types::AsteroidInfo;
+
+    let info: AsteroidInfo = serde_json::from_value(serde_json::json!({
+        "id": 1, "name": "Adalia Prime", "owner": "0x1", "resource_bonuses": { "Iron": 0.15 },
+    }))
+    .unwrap();
+    assert_eq!(info.resource_abundances["Iron"], 0.15);
+
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["resource_abundances"]["Iron"], 0.15);
+    assert!(json.get("resource_bonuses").is_none());
+}